@@ -0,0 +1,355 @@
+use crate::gamepad::{ALL_AXES, ALL_BUTTONS};
+use crate::input::InputState;
+use crate::settings::Settings;
+use sdl2::controller::{Axis, Button};
+use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::mouse::MouseButton;
+use specs::{Read, System, Write};
+use std::collections::HashMap;
+
+///Physical input that can trigger an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    ///Key that produces given character in the current keyboard layout
+    Key(Keycode),
+    ///Key at the given physical location, independent of the keyboard layout
+    Scancode(Scancode),
+    Mouse(MouseButton),
+    ///Button on any connected game controller
+    GamepadButton(Button),
+    ///Stick or trigger pushed past the half of its range, true means positive direction
+    GamepadAxis(Axis, bool),
+}
+
+///Axis value past which `Binding::GamepadAxis` is considered held
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+impl Binding {
+    ///Returns true if bound input is held
+    pub fn is_down(&self, input: &InputState) -> bool {
+        match self {
+            Binding::Key(key) => input.is_key_down(*key),
+            Binding::Scancode(scancode) => input.is_scancode_down(*scancode),
+            Binding::Mouse(button) => input.is_mouse_down(*button),
+            Binding::GamepadButton(button) => input.is_gamepad_button_down(*button),
+            Binding::GamepadAxis(axis, positive) => {
+                let value = input.gamepad_axis(*axis);
+                if *positive {
+                    value > AXIS_PRESS_THRESHOLD
+                } else {
+                    value < -AXIS_PRESS_THRESHOLD
+                }
+            }
+        }
+    }
+
+    ///Returns input that was pressed during current frame.
+    /// Useful for waiting for the new binding in the rebinding menu.
+    /// Keys are preferred over mouse and gamepad buttons, ties are broken by the lowest code
+    pub fn first_just_pressed(input: &InputState) -> Option<Binding> {
+        input
+            .just_pressed_keys()
+            .min_by_key(|key| *key as i32)
+            .map(Binding::Key)
+            .or_else(|| {
+                input
+                    .just_pressed_mouse_buttons()
+                    .min_by_key(|button| *button as i32)
+                    .map(Binding::Mouse)
+            })
+            .or_else(|| {
+                input
+                    .just_pressed_gamepad_buttons()
+                    .min_by_key(|button| *button as i32)
+                    .map(Binding::GamepadButton)
+            })
+    }
+
+    ///Converts binding into a value that can be stored in `Settings`.
+    /// Keys are stored as is, other bindings are stored as negative values
+    pub fn to_value(&self) -> i32 {
+        match self {
+            Binding::Key(key) => *key as i32,
+            Binding::Scancode(scancode) => -((1 << 16) | *scancode as i32),
+            Binding::Mouse(button) => -((2 << 16) | *button as i32),
+            Binding::GamepadButton(button) => -((3 << 16) | *button as i32),
+            Binding::GamepadAxis(axis, positive) => {
+                -((4 << 16) | (*axis as i32) << 1 | *positive as i32)
+            }
+        }
+    }
+
+    ///Reads binding from value created by `to_value`
+    pub fn from_value(value: i32) -> Option<Self> {
+        if value >= 0 {
+            return Keycode::from_i32(value).map(Binding::Key);
+        }
+        let value = value.checked_neg()?;
+        let code = value & 0xffff;
+        match value >> 16 {
+            1 => Scancode::from_i32(code).map(Binding::Scancode),
+            2 => match MouseButton::from_ll(code as u8) {
+                MouseButton::Unknown => None,
+                button => Some(Binding::Mouse(button)),
+            },
+            3 => ALL_BUTTONS
+                .into_iter()
+                .find(|button| *button as i32 == code)
+                .map(Binding::GamepadButton),
+            4 => ALL_AXES
+                .into_iter()
+                .find(|axis| *axis as i32 == code >> 1)
+                .map(|axis| Binding::GamepadAxis(axis, code & 1 == 1)),
+            _ => None,
+        }
+    }
+
+    ///Name that can be displayed to the player
+    pub fn display_name(&self) -> String {
+        match self {
+            Binding::Key(key) => key.name(),
+            Binding::Scancode(scancode) => scancode.name().to_owned(),
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::GamepadButton(button) => format!("Gamepad {}", button.string()),
+            Binding::GamepadAxis(axis, positive) => format!(
+                "Gamepad {}{}",
+                axis.string(),
+                if *positive { "+" } else { "-" }
+            ),
+        }
+    }
+}
+
+///Axis that is controlled by two actions, for example "move_left" and "move_right"
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisBinding {
+    pub negative: String,
+    pub positive: String,
+    ///Controller axis that is used when it is pushed further than the actions
+    pub analog: Option<Axis>,
+}
+
+///State of the action during current frame
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+struct ActionState {
+    down: bool,
+    just_pressed: bool,
+    just_released: bool,
+}
+
+///Resource that maps named actions to the inputs.
+/// Action state is updated from `InputState` by `InputActionSystem`
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct InputActions {
+    bindings: HashMap<String, Vec<Binding>>,
+    axes: HashMap<String, AxisBinding>,
+    states: HashMap<String, ActionState>,
+    axis_values: HashMap<String, f32>,
+}
+
+impl InputActions {
+    ///Declares action with default bindings, replacing any existing bindings
+    pub fn add_action(&mut self, name: &str, bindings: Vec<Binding>) {
+        self.bindings.insert(name.to_owned(), bindings);
+    }
+
+    ///Declares axis that uses value of two actions. Actions are declared if they don't exist yet
+    pub fn add_axis(&mut self, name: &str, negative: &str, positive: &str) {
+        for action in [negative, positive] {
+            self.bindings.entry(action.to_owned()).or_default();
+        }
+        self.axes.insert(
+            name.to_owned(),
+            AxisBinding {
+                negative: negative.to_owned(),
+                positive: positive.to_owned(),
+                analog: None,
+            },
+        );
+    }
+
+    ///Makes axis use analog value of the controller stick or trigger
+    pub fn bind_axis_analog(&mut self, name: &str, analog: Option<Axis>) {
+        if let Some(axis) = self.axes.get_mut(name) {
+            axis.analog = analog;
+        }
+    }
+
+    ///Names of all declared actions sorted alphabetically
+    pub fn actions(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.bindings.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings
+            .get(action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or_default()
+    }
+
+    ///Adds binding to the action
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.bindings.entry(action.to_owned()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    ///Replaces binding at given index or adds it if there is no binding at that index
+    pub fn rebind(&mut self, action: &str, index: usize, binding: Binding) {
+        let bindings = self.bindings.entry(action.to_owned()).or_default();
+        match bindings.get_mut(index) {
+            Some(old) => *old = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    ///Removes all bindings from the action
+    pub fn unbind_all(&mut self, action: &str) {
+        if let Some(bindings) = self.bindings.get_mut(action) {
+            bindings.clear();
+        }
+    }
+
+    ///Returns true if any input bound to the action is held
+    pub fn is_down(&self, action: &str) -> bool {
+        self.states.get(action).is_some_and(|state| state.down)
+    }
+
+    ///Returns true if action started being held during current frame
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.states
+            .get(action)
+            .is_some_and(|state| state.just_pressed)
+    }
+
+    ///Returns true if action stopped being held during current frame
+    pub fn just_released(&self, action: &str) -> bool {
+        self.states
+            .get(action)
+            .is_some_and(|state| state.just_released)
+    }
+
+    ///Value of the axis from -1 to 1
+    pub fn axis(&self, name: &str) -> f32 {
+        self.axis_values.get(name).copied().unwrap_or_default()
+    }
+
+    ///Updates action states using current input
+    pub fn update(&mut self, input: &InputState) {
+        for (name, bindings) in &self.bindings {
+            let down = bindings.iter().any(|binding| binding.is_down(input));
+            let state = self.states.entry(name.clone()).or_default();
+            state.just_pressed = down && !state.down;
+            state.just_released = !down && state.down;
+            state.down = down;
+        }
+        for (name, axis) in &self.axes {
+            let value = |action: &str| {
+                if self.states.get(action).is_some_and(|state| state.down) {
+                    1.0
+                } else {
+                    0.0
+                }
+            };
+            let digital: f32 = value(axis.positive.as_str()) - value(axis.negative.as_str());
+            let analog = axis
+                .analog
+                .map(|analog| input.gamepad_axis(analog))
+                .unwrap_or_default();
+            let result = if analog.abs() > digital.abs() {
+                analog
+            } else {
+                digital
+            };
+            self.axis_values.insert(name.clone(), result);
+        }
+    }
+
+    ///Writes bindings into the settings, so they are saved together with other settings.
+    /// Bindings left from the longer list that was saved before are removed
+    pub fn save_to_settings(&self, settings: &mut Settings) {
+        for (name, bindings) in &self.bindings {
+            let count_name = format!("input_{}_count", name);
+            let old_count = settings
+                .get(count_name.clone())
+                .copied()
+                .unwrap_or_default();
+            for index in bindings.len() as i32..old_count {
+                settings.remove(format!("input_{}_{}", name, index).as_str());
+            }
+            settings.write(count_name, bindings.len() as i32);
+            for (index, binding) in bindings.iter().enumerate() {
+                settings.write(format!("input_{}_{}", name, index), binding.to_value());
+            }
+        }
+    }
+
+    ///Replaces bindings of declared actions with ones stored in the settings.
+    /// Actions that have no stored bindings keep their defaults
+    pub fn load_from_settings(&mut self, settings: &Settings) {
+        for (name, bindings) in &mut self.bindings {
+            let Some(count) = settings.get(format!("input_{}_count", name)) else {
+                continue;
+            };
+            *bindings = (0..*count)
+                .filter_map(|index| settings.get(format!("input_{}_{}", name, index)))
+                .filter_map(|value| Binding::from_value(*value))
+                .collect();
+        }
+    }
+}
+
+///System that updates `InputActions` from `InputState`
+pub struct InputActionSystem;
+
+impl<'a> System<'a> for InputActionSystem {
+    type SystemData = (Write<'a, InputActions>, Read<'a, InputState>);
+
+    fn run(&mut self, (mut actions, input): Self::SystemData) {
+        actions.update(&input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_value_round_trip() {
+        let mut bindings = vec![
+            Binding::Key(Keycode::A),
+            Binding::Key(Keycode::Space),
+            Binding::Key(Keycode::Right),
+            Binding::Key(Keycode::RCtrl),
+            Binding::Scancode(Scancode::W),
+            Binding::Scancode(Scancode::Escape),
+            Binding::Mouse(MouseButton::Left),
+            Binding::Mouse(MouseButton::Middle),
+            Binding::Mouse(MouseButton::X2),
+        ];
+        bindings.extend(ALL_BUTTONS.into_iter().map(Binding::GamepadButton));
+        for axis in ALL_AXES {
+            bindings.push(Binding::GamepadAxis(axis, true));
+            bindings.push(Binding::GamepadAxis(axis, false));
+        }
+        for binding in bindings {
+            assert_eq!(
+                Binding::from_value(binding.to_value()),
+                Some(binding),
+                "{:?}",
+                binding
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_binding_values() {
+        assert_eq!(Binding::from_value(-(5 << 16)), None);
+        assert_eq!(Binding::from_value(-(2 << 16)), None);
+        assert_eq!(Binding::from_value(-((3 << 16) | 0xff)), None);
+        assert_eq!(Binding::from_value(i32::MIN), None);
+    }
+}
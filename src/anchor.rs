@@ -0,0 +1,196 @@
+use crate::components::{Position, Rectangle};
+use crate::hierarchy::{depth, Parent};
+use crate::render::{view_size, Canvas};
+use nalgebra::Vector2;
+use specs::{
+    Component, Entities, Entity, Join, Read, ReadStorage, System, VecStorage, WriteStorage,
+};
+
+///Resource that holds size of the visible area in logical pixels, used for anchoring UI to the screen.
+/// Updated by `render::render_game` every frame, so changes to the window settings are picked up automatically
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScreenSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for ScreenSize {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+        }
+    }
+}
+
+impl ScreenSize {
+    pub fn from_canvas(canvas: &Canvas) -> Result<Self, String> {
+        let (width, height) = view_size(canvas)?;
+        Ok(Self { width, height })
+    }
+}
+
+///Point of the parent rectangle that element is attached to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    ///Position of the anchor as fraction of the parent size
+    pub fn fraction(&self) -> Vector2<f32> {
+        match self {
+            Anchor::TopLeft => Vector2::new(0.0, 0.0),
+            Anchor::Top => Vector2::new(0.5, 0.0),
+            Anchor::TopRight => Vector2::new(1.0, 0.0),
+            Anchor::Left => Vector2::new(0.0, 0.5),
+            Anchor::Center => Vector2::new(0.5, 0.5),
+            Anchor::Right => Vector2::new(1.0, 0.5),
+            Anchor::BottomLeft => Vector2::new(0.0, 1.0),
+            Anchor::Bottom => Vector2::new(0.5, 1.0),
+            Anchor::BottomRight => Vector2::new(1.0, 1.0),
+        }
+    }
+}
+
+///Describes placement of the UI element relative to the screen or to the parent's rectangle.
+/// Corners of the element are placed at the anchors(fractions of parent size) plus pixel offsets,
+/// result is written into `Position` and `Rectangle` by `AnchorSystem`.
+/// Element is placed inside of its `hierarchy::Parent` if parent has `Position` and `Rectangle`,
+/// otherwise it is placed on the whole screen
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct UiTransform {
+    ///Top left corner anchor from 0 to 1
+    pub anchor_min: Vector2<f32>,
+    ///Bottom right corner anchor from 0 to 1
+    pub anchor_max: Vector2<f32>,
+    ///Offset of the top left corner from its anchor in pixels
+    pub offset_min: Vector2<i32>,
+    ///Offset of the bottom right corner from its anchor in pixels
+    pub offset_max: Vector2<i32>,
+}
+
+impl UiTransform {
+    ///Element of fixed size attached to the anchor point, offset moves it away from that point.
+    /// Element is aligned to the anchor, so for example `BottomRight` element stays inside of the parent
+    pub fn anchored(anchor: Anchor, offset: Vector2<i32>, size: Vector2<i32>) -> Self {
+        let fraction = anchor.fraction();
+        let offset_min = offset
+            - Vector2::new(
+                (size.x as f32 * fraction.x) as i32,
+                (size.y as f32 * fraction.y) as i32,
+            );
+        Self {
+            anchor_min: fraction,
+            anchor_max: fraction,
+            offset_min,
+            offset_max: offset_min + size,
+        }
+    }
+
+    ///Element that covers given fractions of the parent, for example (0.1, 0.1) to (0.9, 0.9)
+    pub fn relative(min: Vector2<f32>, max: Vector2<f32>) -> Self {
+        Self {
+            anchor_min: min,
+            anchor_max: max,
+            offset_min: Vector2::new(0, 0),
+            offset_max: Vector2::new(0, 0),
+        }
+    }
+
+    ///Element that fills the parent with given margin on every side
+    pub fn stretch(margin: i32) -> Self {
+        Self {
+            offset_min: Vector2::new(margin, margin),
+            offset_max: Vector2::new(-margin, -margin),
+            ..Self::relative(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0))
+        }
+    }
+
+    ///Resolves position and size of the element inside of the parent rectangle
+    pub fn resolve(
+        &self,
+        origin: Vector2<i32>,
+        size: Vector2<i32>,
+    ) -> (Vector2<i32>, Vector2<i32>) {
+        let point = |anchor: Vector2<f32>| {
+            origin
+                + Vector2::new(
+                    (size.x as f32 * anchor.x).round() as i32,
+                    (size.y as f32 * anchor.y).round() as i32,
+                )
+        };
+        let min = point(self.anchor_min) + self.offset_min;
+        let max = point(self.anchor_max) + self.offset_max;
+        (
+            min,
+            Vector2::new((max.x - min.x).max(0), (max.y - min.y).max(0)),
+        )
+    }
+}
+
+///System that writes `Position` and `Rectangle` of entities with `UiTransform`.
+/// Should run before `layout::LayoutSystem`, `hierarchy::HierarchySystem` and button systems so they use resolved values
+pub struct AnchorSystem;
+
+impl<'a> System<'a> for AnchorSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, UiTransform>,
+        ReadStorage<'a, Parent>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Rectangle>,
+        Read<'a, ScreenSize>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, transforms, parents, mut pos, mut rect, screen): Self::SystemData,
+    ) {
+        let mut elements: Vec<(usize, Entity)> = (&entities, &transforms)
+            .join()
+            .map(|(entity, _)| (depth(&parents, entity), entity))
+            .collect();
+        //parents have to be resolved before their children
+        elements.sort();
+
+        for (_, entity) in elements {
+            let Some(transform) = transforms.get(entity) else {
+                continue;
+            };
+            let (origin, size) = parents
+                .get(entity)
+                .and_then(|parent| pos.get(parent.entity).zip(rect.get(parent.entity)))
+                .map(|(pos, rect)| {
+                    (
+                        Vector2::new(pos.x, pos.y),
+                        Vector2::new(rect.width, rect.height),
+                    )
+                })
+                .unwrap_or_else(|| {
+                    (
+                        Vector2::new(0, 0),
+                        Vector2::new(screen.width as i32, screen.height as i32),
+                    )
+                });
+            let (position, size) = transform.resolve(origin, size);
+            if let Some(pos) = pos.get_mut(entity) {
+                pos.x = position.x;
+                pos.y = position.y;
+            }
+            if let Some(rect) = rect.get_mut(entity) {
+                rect.width = size.x;
+                rect.height = size.y;
+            }
+        }
+    }
+}
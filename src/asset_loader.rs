@@ -0,0 +1,248 @@
+use crate::pack::AssetPack;
+use crate::texture_manager::{Asset, Assets, TextureManager};
+use sdl2::image::{ImageRWops, LoadSurface};
+use sdl2::pixels::PixelFormatEnum;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::prelude::*;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+///Current state of the single asset that is being loaded by the `AssetLoader`
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetStatus {
+    ///Asset is waiting for the free worker
+    Queued,
+    ///Image was decoded and is waiting to be uploaded to the gpu
+    Decoded,
+    ///Texture is available in the texture manager
+    Loaded,
+    ///Asset could not be loaded. Contains error message
+    Failed(String),
+}
+
+///Image that was decoded on the worker thread. Pixels are stored in RGBA32 format
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pitch: usize,
+    pub pixels: Vec<u8>,
+}
+
+///Summary of the loading progress. Can be inserted as a resource to drive loading screen systems
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct LoadingProgress {
+    ///Value from 0 to 1
+    pub progress: f32,
+    pub loaded: usize,
+    pub failed: usize,
+    pub total: usize,
+    ///Name of the last asset that finished loading
+    pub last_finished: Option<String>,
+}
+
+///Where worker threads take the image data from
+#[derive(Clone)]
+pub enum AssetSource {
+    ///Paths are read from disk
+    Files,
+    ///Paths are looked up in the asset pack
+    Pack(Arc<AssetPack>),
+}
+
+type LoadResult = (String, Result<DecodedImage, String>);
+
+///Loads textures in the background. Image decoding happens on worker threads
+/// while textures are created on the main thread when `update` is called
+pub struct AssetLoader {
+    order: Vec<String>,
+    statuses: HashMap<String, AssetStatus>,
+    queue: Arc<Mutex<VecDeque<Asset>>>,
+    receiver: Receiver<LoadResult>,
+    decoded: VecDeque<(String, DecodedImage)>,
+    last_finished: Option<String>,
+}
+
+fn load_surface_from_bytes(bytes: &[u8]) -> Result<sdl2::surface::Surface<'static>, String> {
+    sdl2::rwops::RWops::from_bytes(bytes)?.load()
+}
+
+fn decode(source: &AssetSource, path: &str) -> Result<DecodedImage, String> {
+    let surface = match source {
+        AssetSource::Files => sdl2::surface::Surface::from_file(std::path::Path::new(path))?,
+        AssetSource::Pack(pack) => {
+            let bytes = pack.read(path)?;
+            load_surface_from_bytes(&bytes)?
+        }
+    };
+    let surface = surface.convert_format(PixelFormatEnum::RGBA32)?;
+    let (width, height) = surface.size();
+    let pitch = surface.pitch() as usize;
+    let pixels = surface.with_lock(|buffer: &[u8]| buffer.to_vec());
+    Ok(DecodedImage {
+        width,
+        height,
+        pitch,
+        pixels,
+    })
+}
+
+fn worker(source: AssetSource, queue: Arc<Mutex<VecDeque<Asset>>>, sender: Sender<LoadResult>) {
+    loop {
+        let next = match queue.lock() {
+            Ok(mut queue) => queue.pop_front(),
+            Err(_) => None,
+        };
+        let Some(asset) = next else {
+            return;
+        };
+        let result = decode(&source, asset.path.as_str());
+        if sender.send((asset.name, result)).is_err() {
+            //loader was dropped, nobody needs the results
+            return;
+        }
+    }
+}
+
+impl AssetLoader {
+    ///Starts loading given assets using `threads` worker threads.
+    /// If several assets share a name only the last one is loaded, same as with `TextureManager::load_from_descriptor`
+    pub fn new(assets: Vec<Asset>, source: AssetSource, threads: usize) -> Self {
+        let mut seen = std::collections::HashSet::new();
+        let mut assets: Vec<Asset> = assets
+            .into_iter()
+            .rev()
+            .filter(|asset| seen.insert(asset.name.clone()))
+            .collect();
+        assets.reverse();
+        let order: Vec<String> = assets.iter().map(|a| a.name.clone()).collect();
+        let statuses = order
+            .iter()
+            .map(|name| (name.clone(), AssetStatus::Queued))
+            .collect();
+        let queue = Arc::new(Mutex::new(VecDeque::from(assets)));
+        let (sender, receiver) = channel();
+        for _ in 0..threads.max(1) {
+            let source = source.clone();
+            let queue = queue.clone();
+            let sender = sender.clone();
+            std::thread::spawn(move || worker(source, queue, sender));
+        }
+        Self {
+            order,
+            statuses,
+            queue,
+            receiver,
+            decoded: VecDeque::new(),
+            last_finished: None,
+        }
+    }
+
+    ///Starts loading textures listed in the same asset descriptor that `TextureManager::load_from_descriptor` uses
+    pub fn from_descriptor(threads: usize) -> Result<Self, String> {
+        let mut file = File::open("./assets/assets.json").map_err(|e| e.to_string())?;
+        let mut buf: Vec<u8> = Vec::new();
+        file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+        let data = String::from_utf8(buf).map_err(|e| e.to_string())?;
+        let assets = serde_json::from_str::<Assets>(data.as_str()).map_err(|e| e.to_string())?;
+        Ok(Self::new(assets.textures, AssetSource::Files, threads))
+    }
+
+    ///Starts loading textures listed in the descriptor stored in the asset pack
+    pub fn from_pack(pack: Arc<AssetPack>, threads: usize) -> Result<Self, String> {
+        let data = pack.read_string(crate::pack::DESCRIPTOR_NAME)?;
+        let assets = serde_json::from_str::<Assets>(data.as_str()).map_err(|e| e.to_string())?;
+        Ok(Self::new(assets.textures, AssetSource::Pack(pack), threads))
+    }
+
+    ///Creates textures for images that were decoded since last call.
+    /// This must be called from the main thread, usually once per frame.
+    ///
+    /// max_uploads: limits how many textures are created per call to keep frame time stable
+    pub fn update(&mut self, textures: &mut TextureManager, max_uploads: Option<usize>) {
+        while let Ok((name, result)) = self.receiver.try_recv() {
+            match result {
+                Ok(image) => {
+                    self.statuses.insert(name.clone(), AssetStatus::Decoded);
+                    self.decoded.push_back((name, image));
+                }
+                Err(e) => self.finish(name, AssetStatus::Failed(e)),
+            }
+        }
+        let mut uploaded = 0;
+        while !matches!(max_uploads, Some(max) if uploaded >= max) {
+            let Some((name, image)) = self.decoded.pop_front() else {
+                break;
+            };
+            let status = match textures.load_decoded(name.clone(), &image) {
+                Ok(()) => AssetStatus::Loaded,
+                Err(e) => AssetStatus::Failed(e),
+            };
+            self.finish(name, status);
+            uploaded += 1;
+        }
+    }
+
+    fn finish(&mut self, name: String, status: AssetStatus) {
+        self.statuses.insert(name.clone(), status);
+        self.last_finished = Some(name);
+    }
+
+    pub fn status(&self, name: &str) -> Option<&AssetStatus> {
+        self.statuses.get(name)
+    }
+
+    ///Status of every asset in the order they were requested
+    pub fn statuses(&self) -> impl Iterator<Item = (&String, &AssetStatus)> {
+        self.order
+            .iter()
+            .filter_map(|name| self.statuses.get(name).map(|status| (name, status)))
+    }
+
+    ///Returns value from 0 to 1 that represents how many assets have finished loading
+    pub fn progress(&self) -> f32 {
+        if self.order.is_empty() {
+            return 1.0;
+        }
+        self.finished_count() as f32 / self.order.len() as f32
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_count() == self.order.len()
+    }
+
+    fn finished_count(&self) -> usize {
+        self.statuses
+            .values()
+            .filter(|status| matches!(status, AssetStatus::Loaded | AssetStatus::Failed(_)))
+            .count()
+    }
+
+    ///Returns summary of the current progress
+    pub fn report(&self) -> LoadingProgress {
+        LoadingProgress {
+            progress: self.progress(),
+            loaded: self
+                .statuses
+                .values()
+                .filter(|status| matches!(status, AssetStatus::Loaded))
+                .count(),
+            failed: self
+                .statuses
+                .values()
+                .filter(|status| matches!(status, AssetStatus::Failed(_)))
+                .count(),
+            total: self.order.len(),
+            last_finished: self.last_finished.clone(),
+        }
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        //stop workers from picking up assets that nobody will upload
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.clear();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use nalgebra::{Vector2, Vector4};
 use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
 use specs::{Component, NullStorage, VecStorage, WorldExt};
 
 #[derive(Clone, Copy, Debug, PartialEq, Component)]
@@ -25,6 +26,21 @@ pub struct Sprite {
     pub visible: bool,
 }
 
+///Texture that is split into nine parts using border insets.
+/// Corners keep their size while edges and center are resized to fill entity's `Rectangle`
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct NineSlice {
+    ///Name of the texture that needs to be rendered
+    pub name: String,
+    ///Size of the borders in texture pixels in order: left, top, right, bottom
+    pub insets: Vector4<u32>,
+    ///If true edges and center are repeated instead of being stretched
+    pub tiled: bool,
+    ///Is panel visible
+    pub visible: bool,
+}
+
 ///Represents text that will be displayed on the screen
 #[derive(Clone, Debug, PartialEq, Component)]
 #[storage(VecStorage)]
@@ -51,6 +67,68 @@ pub struct Colored {
     pub color: Color,
 }
 
+///Colors used to draw shapes. Shape is not filled or outlined if color is not set
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeStyle {
+    pub fill: Option<Color>,
+    pub outline: Option<Color>,
+    ///Width of the outline in pixels
+    pub thickness: u32,
+}
+
+impl ShapeStyle {
+    pub fn filled(color: Color) -> Self {
+        Self {
+            fill: Some(color),
+            outline: None,
+            thickness: 1,
+        }
+    }
+
+    pub fn outlined(color: Color, thickness: u32) -> Self {
+        Self {
+            fill: None,
+            outline: Some(color),
+            thickness,
+        }
+    }
+}
+
+///Line that goes from the entity's position to the position + end
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct Line {
+    ///End point relative to the entity's position
+    pub end: Vector2<i32>,
+    pub color: Color,
+    pub thickness: u32,
+}
+
+///Circle centered at the entity's position
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct Circle {
+    pub radius: u32,
+    pub style: ShapeStyle,
+}
+
+///Polygon made of points relative to the entity's position
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct Polygon {
+    pub points: Vec<Vector2<i32>>,
+    pub style: ShapeStyle,
+}
+
+///Rectangle with rounded corners, size is taken from the entity's `Rectangle`
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct RoundedRect {
+    ///Radius of the corners
+    pub radius: u32,
+    pub style: ShapeStyle,
+}
+
 ///Represents simple object that moves along a line
 #[derive(Clone, Debug, PartialEq, Component)]
 #[storage(VecStorage)]
@@ -68,11 +146,43 @@ pub struct AnimationData {
 pub struct Renderable {
     pub visible: bool,
     pub layer: u32,
+    ///How entity is combined with what was drawn before it
+    pub blend_mode: BlendMode,
+    ///Opacity multiplier from 0 to 1
+    pub opacity: f32,
+    ///Opacity of the parents, updated by `hierarchy::HierarchySystem`
+    pub inherited_opacity: f32,
+    ///False if any of the parents in the hierarchy is hidden, updated by `hierarchy::HierarchySystem`
+    pub inherited_visible: bool,
 }
 
 impl Renderable {
     pub fn new(visible: bool, layer: u32) -> Self {
-        Self { visible, layer }
+        Self {
+            visible,
+            layer,
+            ..Default::default()
+        }
+    }
+
+    ///Returns true if both entity and all of its parents are visible
+    pub fn is_visible(&self) -> bool {
+        self.visible && self.inherited_visible
+    }
+
+    ///Opacity that should be used when drawing, includes opacity of the parents
+    pub fn effective_opacity(&self) -> f32 {
+        (self.opacity * self.inherited_opacity).clamp(0.0, 1.0)
+    }
+
+    ///Applies opacity to the alpha value of the color
+    pub fn apply_opacity(&self, color: Color) -> Color {
+        Color::RGBA(
+            color.r,
+            color.g,
+            color.b,
+            (color.a as f32 * self.effective_opacity()).round() as u8,
+        )
     }
 }
 impl Default for Renderable {
@@ -80,6 +190,10 @@ impl Default for Renderable {
         Self {
             visible: true,
             layer: 0,
+            blend_mode: BlendMode::Blend,
+            opacity: 1.0,
+            inherited_opacity: 1.0,
+            inherited_visible: true,
         }
     }
 }
@@ -100,10 +214,21 @@ pub struct SettingsValueDisplay {
 pub fn register_components(world: &mut specs::World) {
     world.register::<Position>();
     world.register::<Sprite>();
+    world.register::<NineSlice>();
+    world.register::<crate::tilemap::Tilemap>();
+    world.register::<crate::particles::ParticleEmitter>();
     world.register::<Text>();
     world.register::<Colored>();
+    world.register::<Line>();
+    world.register::<Circle>();
+    world.register::<Polygon>();
+    world.register::<RoundedRect>();
     world.register::<Rectangle>();
     world.register::<AnimationData>();
     world.register::<Renderable>();
+    world.register::<crate::hierarchy::Parent>();
+    world.register::<crate::hierarchy::Children>();
+    world.register::<crate::hierarchy::LocalPosition>();
+    world.register::<SettingsValue>();
     world.register::<SettingsValueDisplay>();
 }
@@ -0,0 +1,197 @@
+use crate::game::DeltaTime;
+use nalgebra::Vector2;
+use sdl2::pixels::Color;
+use specs::{Read, System, Write};
+
+///Value that linearly changes from one value to another over time
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Transition {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Transition {
+    fn constant(value: f32) -> Self {
+        Self {
+            from: value,
+            to: value,
+            duration: 0.0,
+            elapsed: 0.0,
+        }
+    }
+
+    fn new(from: f32, to: f32, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+        }
+    }
+
+    fn value(&self) -> f32 {
+        if self.is_finished() {
+            self.to
+        } else {
+            self.from + (self.to - self.from) * (self.elapsed / self.duration)
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    fn update(&mut self, delta: f32) {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+    }
+}
+
+///Resource that controls effects drawn on top of the whole screen.
+/// Systems trigger effects using methods of this resource and `ScreenEffectsSystem` animates them
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenEffects {
+    fade_color: Color,
+    fade: Transition,
+    flash_color: Color,
+    flash: Transition,
+    shake: Transition,
+    shake_offset: Vector2<i32>,
+    ///Color of the cutscene bars at the top and the bottom of the screen
+    pub letterbox_color: Color,
+    letterbox: Transition,
+    random_state: u32,
+}
+
+impl Default for ScreenEffects {
+    fn default() -> Self {
+        Self {
+            fade_color: Color::RGBA(0, 0, 0, 0),
+            fade: Transition::constant(0.0),
+            flash_color: Color::WHITE,
+            flash: Transition::constant(0.0),
+            shake: Transition::constant(0.0),
+            shake_offset: Vector2::new(0, 0),
+            letterbox_color: Color::BLACK,
+            letterbox: Transition::constant(0.0),
+            random_state: 0x2545_f491,
+        }
+    }
+}
+
+impl ScreenEffects {
+    ///Fades screen into given color, alpha of the color is the final opacity of the fade
+    pub fn fade_to(&mut self, color: Color, duration: f32) {
+        let current = self.fade.value();
+        self.fade_color = color;
+        self.fade = Transition::new(current, color.a as f32, duration);
+    }
+
+    ///Starts with screen covered by the given color and fades it out
+    pub fn fade_from(&mut self, color: Color, duration: f32) {
+        self.fade_color = color;
+        self.fade = Transition::new(color.a as f32, 0.0, duration);
+    }
+
+    ///Removes fade instantly
+    pub fn clear_fade(&mut self) {
+        self.fade = Transition::constant(0.0);
+    }
+
+    ///Briefly covers the screen with the color that disappears over given time
+    pub fn flash(&mut self, color: Color, duration: f32) {
+        self.flash_color = color;
+        self.flash = Transition::new(color.a as f32, 0.0, duration);
+    }
+
+    ///Shakes the camera, intensity is the maximum offset in pixels and decays to zero over given time.
+    /// Stronger shake is not overridden by the weaker one
+    pub fn shake(&mut self, intensity: f32, duration: f32) {
+        if intensity >= self.shake.value() {
+            self.shake = Transition::new(intensity, 0.0, duration);
+        }
+    }
+
+    ///Slides letterbox bars in until each of them covers given amount of pixels
+    pub fn show_letterbox(&mut self, height: u32, duration: f32) {
+        self.letterbox = Transition::new(self.letterbox.value(), height as f32, duration);
+    }
+
+    ///Slides letterbox bars out
+    pub fn hide_letterbox(&mut self, duration: f32) {
+        self.letterbox = Transition::new(self.letterbox.value(), 0.0, duration);
+    }
+
+    ///Color that should be drawn over the screen for the fade effect
+    pub fn fade_color(&self) -> Color {
+        with_alpha(self.fade_color, self.fade.value())
+    }
+
+    ///Color that should be drawn over the screen for the flash effect
+    pub fn flash_color(&self) -> Color {
+        with_alpha(self.flash_color, self.flash.value())
+    }
+
+    ///Offset that should be added to the camera for the shake effect
+    pub fn shake_offset(&self) -> Vector2<i32> {
+        self.shake_offset
+    }
+
+    ///Current height of each letterbox bar in pixels
+    pub fn letterbox_height(&self) -> u32 {
+        self.letterbox.value().round().max(0.0) as u32
+    }
+
+    ///Returns true if any effect is still animating
+    pub fn is_animating(&self) -> bool {
+        !(self.fade.is_finished()
+            && self.flash.is_finished()
+            && self.shake.is_finished()
+            && self.letterbox.is_finished())
+    }
+
+    fn random(&mut self) -> f32 {
+        self.random_state ^= self.random_state << 13;
+        self.random_state ^= self.random_state >> 17;
+        self.random_state ^= self.random_state << 5;
+        (self.random_state & 0xffffff) as f32 / 0xffffff as f32 * 2.0 - 1.0
+    }
+
+    ///Advances all effects by given time
+    pub fn update(&mut self, delta: f32) {
+        self.fade.update(delta);
+        self.flash.update(delta);
+        self.shake.update(delta);
+        self.letterbox.update(delta);
+        let intensity = self.shake.value();
+        self.shake_offset = if intensity > 0.0 {
+            Vector2::new(
+                (self.random() * intensity).round() as i32,
+                (self.random() * intensity).round() as i32,
+            )
+        } else {
+            Vector2::new(0, 0)
+        };
+    }
+}
+
+fn with_alpha(color: Color, alpha: f32) -> Color {
+    Color::RGBA(
+        color.r,
+        color.g,
+        color.b,
+        alpha.round().clamp(0.0, 255.0) as u8,
+    )
+}
+
+///System that animates `ScreenEffects` using `DeltaTime` resource
+pub struct ScreenEffectsSystem;
+
+impl<'a> System<'a> for ScreenEffectsSystem {
+    type SystemData = (Write<'a, ScreenEffects>, Read<'a, DeltaTime>);
+
+    fn run(&mut self, (mut effects, delta): Self::SystemData) {
+        effects.update(delta.seconds);
+    }
+}
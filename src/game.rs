@@ -1,11 +1,47 @@
 use crate::layers::RenderLayers;
+use nalgebra::Vector2;
 pub struct Game {
     pub active_layers: u32,
+    ///Position of the top left corner of the screen in the world, subtracted from entity positions when drawing
+    pub camera: Vector2<i32>,
+    ///Layers that are drawn in screen space and are not moved by the camera, menus and overlay by default
+    pub screen_space_layers: u32,
+    ///Name of the render target that is being drawn into, sprites that show it are skipped
+    pub(crate) render_target: Option<String>,
+    ///Single layer bit that is drawn by the current render pass, None draws all active layers
+    pub(crate) pass_layer: Option<u32>,
 }
 impl Game {
     pub fn new() -> Self {
         Self {
             active_layers: 0xffffffff,
+            camera: Vector2::new(0, 0),
+            screen_space_layers: RenderLayers::Menu as u32 | RenderLayers::Overlay as u32,
+            render_target: None,
+            pass_layer: None,
+        }
+    }
+
+    ///Highest active layer bit of the entity, entities in several layers are drawn together with this layer
+    pub fn top_layer(&self, layer: u32) -> Option<u32> {
+        let active = layer & self.active_layers;
+        (active != 0).then(|| u32::BITS - 1 - active.leading_zeros())
+    }
+
+    ///Returns true if entity in the given layers is drawn by the current render pass
+    pub fn draws_layer(&self, layer: u32) -> bool {
+        match self.pass_layer {
+            None => layer & self.active_layers != 0,
+            Some(bit) => self.top_layer(layer) == Some(bit),
+        }
+    }
+
+    ///Camera offset that applies to the entity in the given layer
+    pub fn camera_for(&self, layer: u32) -> Vector2<i32> {
+        if layer & self.screen_space_layers != 0 {
+            Vector2::new(0, 0)
+        } else {
+            self.camera
         }
     }
 
@@ -17,3 +53,11 @@ impl Game {
         self.active_layers &= !(layer as u32);
     }
 }
+
+///Resource that holds time passed since the previous frame.
+/// Game loop is expected to update it before dispatching systems
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct DeltaTime {
+    ///Time in seconds
+    pub seconds: f32,
+}
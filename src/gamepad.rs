@@ -0,0 +1,143 @@
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::GameControllerSubsystem;
+use std::collections::HashMap;
+
+///All buttons that a game controller can have, used for converting stored values back to buttons
+pub const ALL_BUTTONS: [Button; 21] = [
+    Button::A,
+    Button::B,
+    Button::X,
+    Button::Y,
+    Button::Back,
+    Button::Guide,
+    Button::Start,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::LeftShoulder,
+    Button::RightShoulder,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+    Button::Misc1,
+    Button::Paddle1,
+    Button::Paddle2,
+    Button::Paddle3,
+    Button::Paddle4,
+    Button::Touchpad,
+];
+
+///All axes that a game controller can have
+pub const ALL_AXES: [Axis; 6] = [
+    Axis::LeftX,
+    Axis::LeftY,
+    Axis::RightX,
+    Axis::RightY,
+    Axis::TriggerLeft,
+    Axis::TriggerRight,
+];
+
+///Keeps connected game controllers open and handles hotplugging.
+/// Button and axis state is tracked by `input::InputState`, this only owns the devices.
+/// Controllers can't be shared between threads so this can not be stored as a resource
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    ///Controllers by their instance id, which is used as `which` in controller events
+    controllers: HashMap<u32, GameController>,
+}
+
+impl Gamepads {
+    ///Initializes game controller subsystem and opens all connected controllers.
+    /// Controllers that fail to open are skipped
+    pub fn new(sdl_context: &sdl2::Sdl) -> Result<Self, String> {
+        let mut result = Self {
+            subsystem: sdl_context.game_controller()?,
+            controllers: HashMap::new(),
+        };
+        for index in 0..result.subsystem.num_joysticks()? {
+            if let Err(e) = result.open(index) {
+                eprintln!("Failed to open game controller {}: {}", index, e);
+            }
+        }
+        Ok(result)
+    }
+
+    fn open(&mut self, joystick_index: u32) -> Result<(), String> {
+        if !self.subsystem.is_game_controller(joystick_index) {
+            return Ok(());
+        }
+        let controller = self
+            .subsystem
+            .open(joystick_index)
+            .map_err(|e| e.to_string())?;
+        self.controllers
+            .insert(controller.instance_id(), controller);
+        Ok(())
+    }
+
+    ///Opens newly connected controllers and closes disconnected ones
+    pub fn process_event(&mut self, event: &Event) -> Result<(), String> {
+        match event {
+            Event::ControllerDeviceAdded { which, .. } => self.open(*which),
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(which);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    ///Adds mappings from the SDL_GameControllerDB format file, returns amount of added mappings
+    pub fn load_mappings(&self, path: &str) -> Result<i32, String> {
+        self.subsystem
+            .load_mappings(path)
+            .map_err(|e| e.to_string())
+    }
+
+    ///Ids of all connected controllers sorted in the order they were connected
+    pub fn connected(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.controllers.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn is_connected(&self, id: u32) -> bool {
+        self.controllers.contains_key(&id)
+    }
+
+    ///Name of the controller as reported by SDL
+    pub fn name(&self, id: u32) -> Option<String> {
+        self.controllers
+            .get(&id)
+            .map(|controller| controller.name())
+    }
+
+    ///Starts rumble, strength values are from 0 to 1
+    pub fn rumble(&mut self, id: u32, low: f32, high: f32, duration_ms: u32) -> Result<(), String> {
+        let controller = self
+            .controllers
+            .get_mut(&id)
+            .ok_or_else(|| format!("Gamepad {} is not connected", id))?;
+        controller
+            .set_rumble(
+                (low.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+                (high.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+                duration_ms,
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    ///Starts rumble on every connected controller.
+    /// Controllers that don't support rumble are ignored
+    pub fn rumble_all(&mut self, low: f32, high: f32, duration_ms: u32) {
+        for id in self.connected() {
+            //not every controller supports rumble so errors are expected
+            let _ = self.rumble(id, low, high, duration_ms);
+        }
+    }
+
+    pub fn stop_rumble(&mut self, id: u32) -> Result<(), String> {
+        self.rumble(id, 0.0, 0.0, 0)
+    }
+}
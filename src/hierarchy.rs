@@ -0,0 +1,192 @@
+use crate::components::{Position, Renderable};
+use specs::{
+    Component, Entities, Entity, Join, ReadStorage, System, VecStorage, World, WorldExt,
+    WriteStorage,
+};
+use std::collections::{HashMap, HashSet};
+
+///Limit on the hierarchy depth, protects against entities that are parents of each other
+const MAX_DEPTH: usize = 64;
+
+///Makes entity a child of another entity.
+/// Children inherit visibility and opacity, are placed relative to the parent when they have `LocalPosition`
+/// or `anchor::UiTransform`, and are deleted together with it by `despawn_recursive`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+#[storage(VecStorage)]
+pub struct Parent {
+    pub entity: Entity,
+}
+
+///Direct children of the entity, kept up to date from `Parent` components by `HierarchySystem`
+#[derive(Clone, Debug, PartialEq, Eq, Default, Component)]
+#[storage(VecStorage)]
+pub struct Children {
+    pub entities: Vec<Entity>,
+}
+
+///Position of the entity relative to the position of its parent.
+/// `Position` of the entity is overwritten by `HierarchySystem` every frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+#[storage(VecStorage)]
+pub struct LocalPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+///Amount of parents above the entity
+pub(crate) fn depth(parents: &ReadStorage<Parent>, entity: Entity) -> usize {
+    let mut depth = 0;
+    let mut current = parents.get(entity);
+    while let Some(parent) = current {
+        depth += 1;
+        if depth >= MAX_DEPTH {
+            break;
+        }
+        current = parents.get(parent.entity);
+    }
+    depth
+}
+
+///System that updates `Children` lists, computes global `Position` from `LocalPosition`
+/// and propagates visibility and opacity from parents to children. Parents are processed before their children.
+///
+/// `anchor::AnchorSystem`, `layout::LayoutSystem` and this system all write `Position`,
+/// they have to run in that order: anchors place containers, layouts place their items
+/// and then children with `LocalPosition` follow their final parents
+pub struct HierarchySystem;
+
+impl<'a> System<'a> for HierarchySystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Parent>,
+        WriteStorage<'a, Children>,
+        ReadStorage<'a, LocalPosition>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Renderable>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, parents, mut children, local, mut pos, mut renderable): Self::SystemData,
+    ) {
+        let mut child_lists: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        let mut ordered: Vec<(usize, Entity)> = Vec::new();
+        for (entity, parent) in (&entities, &parents).join() {
+            if entities.is_alive(parent.entity) {
+                child_lists.entry(parent.entity).or_default().push(entity);
+            }
+            ordered.push((depth(&parents, entity), entity));
+        }
+        for (entity, list) in (&entities, &mut children).join() {
+            list.entities = child_lists.remove(&entity).unwrap_or_default();
+        }
+        for (parent, list) in child_lists {
+            //error only happens if parent is dead, such parents are filtered out above
+            let _ = children.insert(parent, Children { entities: list });
+        }
+
+        //entities that were detached from the parent become visible and opaque again
+        for (rend, ()) in (&mut renderable, !&parents).join() {
+            rend.inherited_visible = true;
+            rend.inherited_opacity = 1.0;
+        }
+        ordered.sort();
+        //opacity and visibility that children of the entity inherit, entities without `Renderable` pass them through
+        let mut inherited: HashMap<Entity, (f32, bool)> = HashMap::new();
+        for (_, entity) in ordered {
+            let Some(parent) = parents.get(entity).map(|parent| parent.entity) else {
+                continue;
+            };
+            if let (Some(local), Some(parent_pos)) = (local.get(entity), pos.get(parent).copied()) {
+                if let Some(pos) = pos.get_mut(entity) {
+                    pos.x = parent_pos.x + local.x;
+                    pos.y = parent_pos.y + local.y;
+                }
+            }
+            let (opacity, visible) = inherited.get(&parent).copied().unwrap_or_else(|| {
+                renderable
+                    .get(parent)
+                    .map(|rend| (rend.opacity, rend.visible))
+                    .unwrap_or((1.0, true))
+            });
+            let passed = match renderable.get_mut(entity) {
+                Some(rend) => {
+                    rend.inherited_opacity = opacity;
+                    rend.inherited_visible = visible;
+                    (opacity * rend.opacity, visible && rend.visible)
+                }
+                None => (opacity, visible),
+            };
+            inherited.insert(entity, passed);
+        }
+    }
+}
+
+///Returns entity and all of its descendants, parents come before their children.
+/// Children are found from `Parent` components, so it doesn't depend on `HierarchySystem` having run
+pub fn descendants(world: &World, entity: Entity) -> Vec<Entity> {
+    let entities = world.entities();
+    let parents = world.read_storage::<Parent>();
+    let mut child_lists: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (child, parent) in (&entities, &parents).join() {
+        child_lists.entry(parent.entity).or_default().push(child);
+    }
+    let mut visited: HashSet<Entity> = HashSet::from([entity]);
+    let mut result = vec![entity];
+    let mut index = 0;
+    while let Some(current) = result.get(index).copied() {
+        if let Some(list) = child_lists.get(&current) {
+            result.extend(
+                list.iter()
+                    .filter(|child| visited.insert(**child))
+                    .copied()
+                    .collect::<Vec<Entity>>(),
+            );
+        }
+        index += 1;
+    }
+    result
+}
+
+///Deletes entity together with all of its descendants
+pub fn despawn_recursive(world: &mut World, entity: Entity) -> Result<(), String> {
+    let family = descendants(world, entity);
+    world.delete_entities(&family).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specs::Builder;
+
+    #[test]
+    fn despawn_without_hierarchy_system() {
+        let mut world = World::new();
+        world.register::<Parent>();
+        let root = world.create_entity().build();
+        let child = world.create_entity().with(Parent { entity: root }).build();
+        let grandchild = world.create_entity().with(Parent { entity: child }).build();
+        let other = world.create_entity().build();
+        world.maintain();
+
+        assert_eq!(descendants(&world, root), vec![root, child, grandchild]);
+        despawn_recursive(&mut world, root).unwrap();
+        world.maintain();
+        assert!(!world.is_alive(child));
+        assert!(!world.is_alive(grandchild));
+        assert!(world.is_alive(other));
+    }
+
+    #[test]
+    fn parent_cycle() {
+        let mut world = World::new();
+        world.register::<Parent>();
+        let first = world.create_entity().build();
+        let second = world.create_entity().with(Parent { entity: first }).build();
+        world
+            .write_storage::<Parent>()
+            .insert(first, Parent { entity: second })
+            .unwrap();
+        assert_eq!(descendants(&world, first), vec![first, second]);
+    }
+}
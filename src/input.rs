@@ -0,0 +1,351 @@
+use nalgebra::Vector2;
+use sdl2::controller::{Axis, Button};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::MouseButton;
+use sdl2::EventPump;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+///Tracks which buttons are held and which changed state during current frame
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ButtonStates<T: Hash + Eq + Copy> {
+    down: HashSet<T>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+
+impl<T: Hash + Eq + Copy> Default for ButtonStates<T> {
+    fn default() -> Self {
+        Self {
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Copy> ButtonStates<T> {
+    fn press(&mut self, button: T) {
+        if self.down.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: T) {
+        if self.down.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    fn release_all(&mut self) {
+        self.released.extend(self.down.drain());
+    }
+
+    fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+}
+
+///State of the single game controller
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct GamepadState {
+    buttons: ButtonStates<Button>,
+    ///Raw axis values from -1 to 1, deadzone is applied when reading them
+    axes: HashMap<Axis, f32>,
+}
+
+impl GamepadState {
+    pub fn is_button_down(&self, button: Button) -> bool {
+        self.buttons.down.contains(&button)
+    }
+
+    pub fn is_button_just_pressed(&self, button: Button) -> bool {
+        self.buttons.pressed.contains(&button)
+    }
+
+    pub fn is_button_just_released(&self, button: Button) -> bool {
+        self.buttons.released.contains(&button)
+    }
+
+    ///Value of the axis with deadzone applied. Sticks are from -1 to 1 and triggers are from 0 to 1
+    pub fn axis(&self, axis: Axis, deadzone: f32) -> f32 {
+        let value = self.axes.get(&axis).copied().unwrap_or_default();
+        if value.abs() <= deadzone {
+            0.0
+        } else {
+            //rescale so that value starts from zero at the edge of the deadzone
+            value.signum() * (value.abs() - deadzone) / (1.0 - deadzone).max(f32::EPSILON)
+        }
+    }
+}
+
+///Resource that holds state of the mouse, keyboard and game controllers.
+/// Should be updated once per frame before dispatching systems, either using `update`
+/// or `begin_frame` followed by `process_event` for every event
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputState {
+    ///Mouse position in logical coordinates
+    pub mouse_position: Vector2<i32>,
+    ///Mouse movement during current frame
+    pub mouse_motion: Vector2<i32>,
+    ///Wheel scroll during current frame, positive y is scroll away from the user
+    pub wheel: Vector2<i32>,
+    ///Currently held keyboard modifiers
+    pub modifiers: Mod,
+    ///True if window was asked to close during current frame
+    pub quit_requested: bool,
+    ///Axis values with smaller magnitude are treated as zero
+    pub gamepad_deadzone: f32,
+    mouse_buttons: ButtonStates<MouseButton>,
+    keycodes: ButtonStates<Keycode>,
+    scancodes: ButtonStates<Scancode>,
+    ///Gamepad states by controller instance id
+    gamepads: HashMap<u32, GamepadState>,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            mouse_position: Vector2::new(0, 0),
+            mouse_motion: Vector2::new(0, 0),
+            wheel: Vector2::new(0, 0),
+            modifiers: Mod::NOMOD,
+            quit_requested: false,
+            gamepad_deadzone: 0.2,
+            mouse_buttons: ButtonStates::default(),
+            keycodes: ButtonStates::default(),
+            scancodes: ButtonStates::default(),
+            gamepads: HashMap::new(),
+        }
+    }
+}
+
+impl InputState {
+    ///Clears values that only last for one frame
+    pub fn begin_frame(&mut self) {
+        self.mouse_motion = Vector2::new(0, 0);
+        self.wheel = Vector2::new(0, 0);
+        self.quit_requested = false;
+        self.mouse_buttons.begin_frame();
+        self.keycodes.begin_frame();
+        self.scancodes.begin_frame();
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.buttons.begin_frame();
+        }
+    }
+
+    ///Updates state using the event. Mouse coordinates of events are already in logical space
+    pub fn process_event(&mut self, event: &Event) {
+        match event {
+            Event::Quit { .. } => self.quit_requested = true,
+            Event::KeyDown {
+                keycode,
+                scancode,
+                keymod,
+                ..
+            } => {
+                if let Some(keycode) = keycode {
+                    self.keycodes.press(*keycode);
+                }
+                if let Some(scancode) = scancode {
+                    self.scancodes.press(*scancode);
+                }
+                self.modifiers = *keymod;
+            }
+            Event::KeyUp {
+                keycode,
+                scancode,
+                keymod,
+                ..
+            } => {
+                if let Some(keycode) = keycode {
+                    self.keycodes.release(*keycode);
+                }
+                if let Some(scancode) = scancode {
+                    self.scancodes.release(*scancode);
+                }
+                self.modifiers = *keymod;
+            }
+            Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => {
+                self.mouse_position = Vector2::new(*x, *y);
+                self.mouse_motion += Vector2::new(*xrel, *yrel);
+            }
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => {
+                self.mouse_position = Vector2::new(*x, *y);
+                self.mouse_buttons.press(*mouse_btn);
+            }
+            Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => {
+                self.mouse_position = Vector2::new(*x, *y);
+                self.mouse_buttons.release(*mouse_btn);
+            }
+            Event::MouseWheel { x, y, .. } => {
+                self.wheel += Vector2::new(*x, *y);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.gamepads
+                    .entry(*which)
+                    .or_default()
+                    .buttons
+                    .press(*button);
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.gamepads
+                    .entry(*which)
+                    .or_default()
+                    .buttons
+                    .release(*button);
+            }
+            Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                let value = (*value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+                self.gamepads
+                    .entry(*which)
+                    .or_default()
+                    .axes
+                    .insert(*axis, value);
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.gamepads.remove(which);
+            }
+            //otherwise keys that were released while window was not focused will stay held
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                self.mouse_buttons.release_all();
+                self.keycodes.release_all();
+                self.scancodes.release_all();
+                self.modifiers = Mod::NOMOD;
+            }
+            _ => {}
+        }
+    }
+
+    ///Starts new frame and processes all pending events.
+    /// Events are returned so game can handle them as well
+    pub fn update(&mut self, event_pump: &mut EventPump) -> Vec<Event> {
+        self.begin_frame();
+        let events: Vec<Event> = event_pump.poll_iter().collect();
+        for event in &events {
+            self.process_event(event);
+        }
+        events
+    }
+
+    pub fn is_key_down(&self, key: Keycode) -> bool {
+        self.keycodes.down.contains(&key)
+    }
+
+    ///Returns true if key was pressed during current frame
+    pub fn is_key_just_pressed(&self, key: Keycode) -> bool {
+        self.keycodes.pressed.contains(&key)
+    }
+
+    ///Returns true if key was released during current frame
+    pub fn is_key_just_released(&self, key: Keycode) -> bool {
+        self.keycodes.released.contains(&key)
+    }
+
+    ///Scancodes represent physical key location and do not depend on keyboard layout
+    pub fn is_scancode_down(&self, scancode: Scancode) -> bool {
+        self.scancodes.down.contains(&scancode)
+    }
+
+    pub fn is_scancode_just_pressed(&self, scancode: Scancode) -> bool {
+        self.scancodes.pressed.contains(&scancode)
+    }
+
+    pub fn is_scancode_just_released(&self, scancode: Scancode) -> bool {
+        self.scancodes.released.contains(&scancode)
+    }
+
+    ///Keys that were pressed during current frame
+    pub fn just_pressed_keys(&self) -> impl Iterator<Item = Keycode> + '_ {
+        self.keycodes.pressed.iter().copied()
+    }
+
+    ///Mouse buttons that were pressed during current frame
+    pub fn just_pressed_mouse_buttons(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        self.mouse_buttons.pressed.iter().copied()
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.down.contains(&button)
+    }
+
+    pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.pressed.contains(&button)
+    }
+
+    pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.released.contains(&button)
+    }
+
+    ///State of the controller with given instance id
+    pub fn gamepad(&self, id: u32) -> Option<&GamepadState> {
+        self.gamepads.get(&id)
+    }
+
+    ///Ids of controllers that sent any input
+    pub fn gamepad_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.gamepads.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    ///Returns true if button is held on any controller
+    pub fn is_gamepad_button_down(&self, button: Button) -> bool {
+        self.gamepads
+            .values()
+            .any(|gamepad| gamepad.is_button_down(button))
+    }
+
+    ///Returns true if button was pressed on any controller during current frame
+    pub fn is_gamepad_button_just_pressed(&self, button: Button) -> bool {
+        self.gamepads
+            .values()
+            .any(|gamepad| gamepad.is_button_just_pressed(button))
+    }
+
+    ///Value of the axis with the largest magnitude among all controllers
+    pub fn gamepad_axis(&self, axis: Axis) -> f32 {
+        self.gamepads
+            .values()
+            .map(|gamepad| gamepad.axis(axis, self.gamepad_deadzone))
+            .fold(0.0, |result: f32, value| {
+                if value.abs() > result.abs() {
+                    value
+                } else {
+                    result
+                }
+            })
+    }
+
+    ///Gamepad buttons that were pressed on any controller during current frame
+    pub fn just_pressed_gamepad_buttons(&self) -> impl Iterator<Item = Button> + '_ {
+        self.gamepads
+            .values()
+            .flat_map(|gamepad| gamepad.buttons.pressed.iter().copied())
+    }
+
+    pub fn shift(&self) -> bool {
+        self.modifiers.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD)
+    }
+
+    pub fn ctrl(&self) -> bool {
+        self.modifiers.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD)
+    }
+
+    pub fn alt(&self) -> bool {
+        self.modifiers.intersects(Mod::LALTMOD | Mod::RALTMOD)
+    }
+}
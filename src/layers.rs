@@ -1,5 +1,8 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RenderLayers{
     Background = 1 << 1,
     Gameplay = 1 << 2, 
     Menu = 1 << 3,
+    ///Drawn above everything else, used for things like opened dropdown lists
+    Overlay = 1 << 4,
 }
\ No newline at end of file
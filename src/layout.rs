@@ -0,0 +1,400 @@
+use crate::components::{Position, Rectangle, Renderable};
+use nalgebra::{Vector2, Vector4};
+use specs::{Component, Entities, Entity, Join, ReadStorage, System, VecStorage, WriteStorage};
+
+///How children of the layout are placed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutKind {
+    ///Children are placed from top to bottom
+    Vertical,
+    ///Children are placed from left to right
+    Horizontal,
+    ///Children are placed row by row into cells of equal width
+    Grid { columns: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Alignment {
+    #[default]
+    Start,
+    Center,
+    End,
+    ///Child is resized to take all available space
+    Stretch,
+}
+
+///How size of the child along the layout direction is decided
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum SizePolicy {
+    ///Child keeps size of its `Rectangle`
+    #[default]
+    Fixed,
+    ///Child takes share of the space left after fixed children, proportional to the weight
+    Fill(f32),
+}
+
+///Container that places child entities inside of its `Position` and `Rectangle`.
+/// Children are marked with `LayoutItem` component
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct Layout {
+    pub kind: LayoutKind,
+    ///Space between edges of the container and children: left, top, right, bottom
+    pub padding: Vector4<i32>,
+    ///Space between children
+    pub spacing: i32,
+    ///Alignment of the children along the layout direction, stretch is treated as start
+    pub main_alignment: Alignment,
+    ///Alignment of the children across the layout direction or inside of the grid cell
+    pub cross_alignment: Alignment,
+}
+
+impl Layout {
+    pub fn new(kind: LayoutKind) -> Self {
+        Self {
+            kind,
+            padding: Vector4::new(0, 0, 0, 0),
+            spacing: 0,
+            main_alignment: Alignment::Start,
+            cross_alignment: Alignment::Start,
+        }
+    }
+
+    pub fn vertical(spacing: i32) -> Self {
+        Self {
+            spacing,
+            ..Self::new(LayoutKind::Vertical)
+        }
+    }
+
+    pub fn horizontal(spacing: i32) -> Self {
+        Self {
+            spacing,
+            ..Self::new(LayoutKind::Horizontal)
+        }
+    }
+
+    pub fn grid(columns: u32, spacing: i32) -> Self {
+        Self {
+            spacing,
+            ..Self::new(LayoutKind::Grid { columns })
+        }
+    }
+}
+
+///Marks entity as a child of the layout container
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct LayoutItem {
+    pub container: Entity,
+    ///Children are placed in the ascending order
+    pub order: i32,
+    pub size: SizePolicy,
+    ///Size of the child's `Rectangle` before layout resized it. Stretched children are measured by it,
+    /// so they can shrink again. Taken again whenever something other than the layout changes the rectangle
+    natural_size: Option<Vector2<i32>>,
+    ///Size that layout wrote into the child's `Rectangle` the last time
+    placed_size: Option<Vector2<i32>>,
+}
+
+impl LayoutItem {
+    pub fn new(container: Entity, order: i32) -> Self {
+        Self {
+            container,
+            order,
+            size: SizePolicy::Fixed,
+            natural_size: None,
+            placed_size: None,
+        }
+    }
+
+    ///Size of the child that layout should use, given the current size of its `Rectangle`
+    fn measure(&mut self, current: Vector2<i32>) -> Vector2<i32> {
+        let natural = match (self.natural_size, self.placed_size) {
+            (Some(natural), Some(placed)) if placed == current => natural,
+            _ => current,
+        };
+        self.natural_size = Some(natural);
+        natural
+    }
+}
+
+///Position and size of the child inside of the available space along one axis
+fn align(alignment: Alignment, start: i32, available: i32, size: i32) -> (i32, i32) {
+    match alignment {
+        Alignment::Start => (start, size),
+        Alignment::Center => (start + (available - size) / 2, size),
+        Alignment::End => (start + available - size, size),
+        Alignment::Stretch => (start, available),
+    }
+}
+
+///Places children along one axis. Sizes are (main, cross) and result is (main position, cross position, main size, cross size)
+fn stack(
+    layout: &Layout,
+    origin: (i32, i32),
+    available: (i32, i32),
+    children: &[(SizePolicy, (i32, i32))],
+) -> Vec<(i32, i32, i32, i32)> {
+    let count = children.len() as i32;
+    let spacing_total = layout.spacing * (count - 1).max(0);
+    let fixed_total: i32 = children
+        .iter()
+        .filter(|(policy, _)| *policy == SizePolicy::Fixed)
+        .map(|(_, size)| size.0)
+        .sum();
+    let weight_total: f32 = children
+        .iter()
+        .map(|(policy, _)| match policy {
+            SizePolicy::Fill(weight) => weight.max(0.0),
+            SizePolicy::Fixed => 0.0,
+        })
+        .sum();
+    let remaining = (available.0 - fixed_total - spacing_total).max(0);
+    let main_sizes: Vec<i32> = children
+        .iter()
+        .map(|(policy, size)| match policy {
+            SizePolicy::Fixed => size.0,
+            SizePolicy::Fill(weight) if weight_total > 0.0 => {
+                (remaining as f32 * weight.max(0.0) / weight_total) as i32
+            }
+            SizePolicy::Fill(_) => 0,
+        })
+        .collect();
+    let used: i32 = main_sizes.iter().sum::<i32>() + spacing_total;
+    let (mut main, _) = align(
+        match layout.main_alignment {
+            Alignment::Stretch => Alignment::Start,
+            alignment => alignment,
+        },
+        origin.0,
+        available.0,
+        used,
+    );
+    children
+        .iter()
+        .zip(main_sizes)
+        .map(|((_, size), main_size)| {
+            let (cross, cross_size) = align(layout.cross_alignment, origin.1, available.1, size.1);
+            let result = (main, cross, main_size, cross_size);
+            main += main_size + layout.spacing;
+            result
+        })
+        .collect()
+}
+
+///Places children into the grid cells. Rows are as tall as the tallest child in them, measured by natural sizes
+fn grid(
+    layout: &Layout,
+    columns: u32,
+    origin: Vector2<i32>,
+    available: Vector2<i32>,
+    children: &[(SizePolicy, Vector2<i32>)],
+) -> Vec<(Vector2<i32>, Vector2<i32>)> {
+    let columns = columns.max(1) as usize;
+    let cell_width = (available.x - layout.spacing * (columns as i32 - 1)) / columns as i32;
+    let mut result = Vec::with_capacity(children.len());
+    let mut y = origin.y;
+    for row in children.chunks(columns) {
+        let row_height = row.iter().map(|(_, size)| size.y).max().unwrap_or_default();
+        for (column, (policy, size)) in row.iter().enumerate() {
+            let alignment = match policy {
+                SizePolicy::Fill(_) => Alignment::Stretch,
+                SizePolicy::Fixed => layout.cross_alignment,
+            };
+            let cell_x = origin.x + column as i32 * (cell_width + layout.spacing);
+            let (x, width) = align(alignment, cell_x, cell_width, size.x);
+            let (y, height) = align(alignment, y, row_height, size.y);
+            result.push((Vector2::new(x, y), Vector2::new(width, height)));
+        }
+        y += row_height + layout.spacing;
+    }
+    result
+}
+
+///System that assigns `Position` and `Rectangle` to children of layout containers every frame.
+/// Containers can be nested, outer containers are processed first. Hidden children are skipped
+pub struct LayoutSystem;
+
+impl<'a> System<'a> for LayoutSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Layout>,
+        WriteStorage<'a, LayoutItem>,
+        ReadStorage<'a, Renderable>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Rectangle>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, layouts, mut items, renderable, mut pos, mut rect): Self::SystemData,
+    ) {
+        //limit on the depth protects against containers that contain each other
+        const MAX_DEPTH: usize = 32;
+        let mut containers: Vec<(usize, Entity)> = (&entities, &layouts)
+            .join()
+            .map(|(entity, _)| {
+                let mut depth = 0;
+                let mut current = items.get(entity);
+                while let Some(item) = current {
+                    depth += 1;
+                    if depth >= MAX_DEPTH {
+                        break;
+                    }
+                    current = items.get(item.container);
+                }
+                (depth, entity)
+            })
+            .collect();
+        containers.sort();
+
+        for (_, container) in containers {
+            let (Some(layout), Some(container_pos), Some(container_rect)) = (
+                layouts.get(container),
+                pos.get(container).cloned(),
+                rect.get(container).cloned(),
+            ) else {
+                continue;
+            };
+            //rectangles of stretched children were overwritten by the layout, so natural sizes are used instead
+            let mut children: Vec<(i32, Entity, SizePolicy, Vector2<i32>)> =
+                (&entities, &mut items, &rect, (&renderable).maybe())
+                    .join()
+                    .filter(|(_, item, _, rend)| {
+                        item.container == container
+                            && rend.map(|rend| rend.is_visible()).unwrap_or(true)
+                    })
+                    .map(|(entity, item, rect, _)| {
+                        let size = item.measure(Vector2::new(rect.width, rect.height));
+                        (item.order, entity, item.size, size)
+                    })
+                    .collect();
+            children.sort_by_key(|(order, entity, ..)| (*order, entity.id()));
+
+            let origin = Vector2::new(
+                container_pos.x + layout.padding.x,
+                container_pos.y + layout.padding.y,
+            );
+            let available = Vector2::new(
+                container_rect.width - layout.padding.x - layout.padding.z,
+                container_rect.height - layout.padding.y - layout.padding.w,
+            );
+            let placed: Vec<(Vector2<i32>, Vector2<i32>)> = match layout.kind {
+                LayoutKind::Vertical => stack(
+                    layout,
+                    (origin.y, origin.x),
+                    (available.y, available.x),
+                    &children
+                        .iter()
+                        .map(|(_, _, policy, size)| (*policy, (size.y, size.x)))
+                        .collect::<Vec<_>>(),
+                )
+                .into_iter()
+                .map(|(y, x, height, width)| (Vector2::new(x, y), Vector2::new(width, height)))
+                .collect(),
+                LayoutKind::Horizontal => stack(
+                    layout,
+                    (origin.x, origin.y),
+                    (available.x, available.y),
+                    &children
+                        .iter()
+                        .map(|(_, _, policy, size)| (*policy, (size.x, size.y)))
+                        .collect::<Vec<_>>(),
+                )
+                .into_iter()
+                .map(|(x, y, width, height)| (Vector2::new(x, y), Vector2::new(width, height)))
+                .collect(),
+                LayoutKind::Grid { columns } => grid(
+                    layout,
+                    columns,
+                    origin,
+                    available,
+                    &children
+                        .iter()
+                        .map(|(_, _, policy, size)| (*policy, *size))
+                        .collect::<Vec<_>>(),
+                ),
+            };
+            for ((_, entity, ..), (position, size)) in children.iter().zip(placed) {
+                if let Some(pos) = pos.get_mut(*entity) {
+                    pos.x = position.x;
+                    pos.y = position.y;
+                }
+                if let Some(rect) = rect.get_mut(*entity) {
+                    rect.width = size.x;
+                    rect.height = size.y;
+                }
+                if let Some(item) = items.get_mut(*entity) {
+                    item.placed_size = Some(size);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specs::{Builder, RunNow, World, WorldExt};
+
+    #[test]
+    fn stretched_child_keeps_natural_size() {
+        let mut world = World::new();
+        let mut item = LayoutItem::new(world.create_entity().build(), 0);
+        assert_eq!(item.measure(Vector2::new(10, 20)), Vector2::new(10, 20));
+        //layout stretched the child, rectangle now holds the stretched size
+        item.placed_size = Some(Vector2::new(100, 20));
+        assert_eq!(item.measure(Vector2::new(100, 20)), Vector2::new(10, 20));
+        //something else resized the child, that becomes its new natural size
+        assert_eq!(item.measure(Vector2::new(30, 5)), Vector2::new(30, 5));
+    }
+
+    #[test]
+    fn grid_rows_shrink_with_children() {
+        let mut world = World::new();
+        world.register::<Layout>();
+        world.register::<LayoutItem>();
+        world.register::<Renderable>();
+        world.register::<Position>();
+        world.register::<Rectangle>();
+        let mut layout = Layout::grid(2, 0);
+        layout.cross_alignment = Alignment::Stretch;
+        let container = world
+            .create_entity()
+            .with(layout)
+            .with(Position { x: 0, y: 0 })
+            .with(Rectangle {
+                width: 100,
+                height: 100,
+            })
+            .build();
+        let mut child = |height: i32| {
+            world
+                .create_entity()
+                .with(LayoutItem::new(container, 0))
+                .with(Position { x: 0, y: 0 })
+                .with(Rectangle { width: 10, height })
+                .build()
+        };
+        let (tall, short) = (child(30), child(10));
+        let height = |world: &World, entity| {
+            world
+                .read_storage::<Rectangle>()
+                .get(entity)
+                .unwrap()
+                .height
+        };
+
+        LayoutSystem.run_now(&world);
+        assert_eq!(height(&world, short), 30);
+        world
+            .write_storage::<Rectangle>()
+            .get_mut(tall)
+            .unwrap()
+            .height = 10;
+        LayoutSystem.run_now(&world);
+        assert_eq!(height(&world, tall), 10);
+        assert_eq!(height(&world, short), 10);
+    }
+}
@@ -5,4 +5,20 @@ pub mod settings;
 pub mod ui;
 pub mod texture_manager;
 pub mod setup;
-pub mod game;
\ No newline at end of file
+pub mod game;
+pub mod pack;
+pub mod asset_loader;
+pub mod procedural;
+pub mod tilemap;
+pub mod tiled;
+pub mod primitives;
+pub mod particles;
+pub mod effects;
+pub mod screenshot;
+pub mod input;
+pub mod actions;
+pub mod gamepad;
+pub mod settings_menu;
+pub mod layout;
+pub mod anchor;
+pub mod hierarchy;
\ No newline at end of file
@@ -1,3 +1,59 @@
+use game_oxide_framework::pack::{pack_descriptor, AssetPack};
+
+fn print_usage() {
+    println!("Game Oxide Framework asset tool");
+    println!();
+    println!("Usage:");
+    println!("  pack [descriptor] [output] [extra files...]");
+    println!("      Packs textures listed in descriptor(default ./assets/assets.json)");
+    println!("      into output(default ./assets.pak). Extra files such as settings.json");
+    println!("      and settings_info.json are stored under their own paths");
+    println!("  list <pack>");
+    println!("      Prints files stored in the pack");
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args.first().map(|s| s.as_str()) {
+        Some("pack") => {
+            let descriptor = args
+                .get(1)
+                .cloned()
+                .unwrap_or_else(|| "./assets/assets.json".to_owned());
+            let output = args
+                .get(2)
+                .cloned()
+                .unwrap_or_else(|| "./assets.pak".to_owned());
+            let extra = if args.len() > 3 { &args[3..] } else { &[] };
+            let count = pack_descriptor(descriptor.as_str(), extra, output.as_str())?;
+            println!("Packed {} files into {}", count, output);
+            Ok(())
+        }
+        Some("list") => {
+            let path = args.get(1).ok_or("Missing path to the pack")?;
+            let pack = AssetPack::open(path.as_str())?;
+            let mut names: Vec<&String> = pack.names().collect();
+            names.sort();
+            for name in names {
+                if let Some(entry) = pack.get_entry(name.as_str()) {
+                    println!(
+                        "{} ({} -> {} bytes)",
+                        name, entry.size, entry.compressed_size
+                    );
+                }
+            }
+            Ok(())
+        }
+        _ => {
+            print_usage();
+            Ok(())
+        }
+    }
+}
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,360 @@
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+///Magic bytes that every asset pack starts with
+pub const PACK_MAGIC: &[u8; 4] = b"GOFP";
+///Version of the pack layout written by this version of the framework
+pub const PACK_VERSION: u32 = 1;
+///Name under which the asset descriptor is stored inside of the pack
+pub const DESCRIPTOR_NAME: &str = "assets.json";
+
+///Information about single file stored in the pack
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackEntry {
+    ///Offset of the compressed blob from the start of the data section
+    pub offset: u64,
+    ///Size of the compressed blob
+    pub compressed_size: u64,
+    ///Size of the file after decompression
+    pub size: u64,
+    ///Crc32 of the uncompressed file
+    pub checksum: u32,
+}
+
+///Asset pack is a single binary file that holds compressed copies of asset files.
+///
+/// Layout is: magic, version, entry count, index, index checksum and then compressed blobs.
+/// All numbers are stored as little endian
+pub struct AssetPack {
+    entries: HashMap<String, PackEntry>,
+    ///Whole pack file, blobs start at `data_start`
+    data: Vec<u8>,
+    data_start: usize,
+}
+
+///Builder for asset packs. Files are compressed when pack is written
+#[derive(Default)]
+pub struct AssetPackWriter {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+///Converts path into the name used for lookup inside of the pack
+/// so that "./assets/a.png" and "assets/a.png" refer to the same entry
+pub fn normalize_name(name: &str) -> String {
+    let name = name.replace('\\', "/");
+    let mut name = name.as_str();
+    while let Some(stripped) = name.strip_prefix("./") {
+        name = stripped;
+    }
+    name.to_owned()
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+///Small helper for reading little endian values from the pack header
+struct PackReader<'a> {
+    data: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> PackReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.cursor + len > self.data.len() {
+            return Err("Asset pack is truncated".to_owned());
+        }
+        let result = &self.data[self.cursor..self.cursor + len];
+        self.cursor += len;
+        Ok(result)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, String> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+impl AssetPack {
+    ///Reads whole pack file into memory
+    pub fn open(path: &str) -> Result<Self, String> {
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        let mut buf: Vec<u8> = Vec::new();
+        file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+        Self::from_bytes(buf)
+    }
+
+    ///Parses pack from raw bytes, validating header and index checksum
+    pub fn from_bytes(buf: Vec<u8>) -> Result<Self, String> {
+        let mut reader = PackReader {
+            data: &buf,
+            cursor: 0,
+        };
+        if reader.take(4)? != PACK_MAGIC {
+            return Err("File is not an asset pack".to_owned());
+        }
+        let version = reader.read_u32()?;
+        if version != PACK_VERSION {
+            return Err(format!(
+                "Unsupported asset pack version {}, expected {}",
+                version, PACK_VERSION
+            ));
+        }
+        let index_start = reader.cursor;
+        let count = reader.read_u32()?;
+        let mut raw_entries: Vec<(&[u8], PackEntry)> = Vec::new();
+        for _ in 0..count {
+            let name_len = reader.read_u16()? as usize;
+            let name = reader.take(name_len)?;
+            let entry = PackEntry {
+                offset: reader.read_u64()?,
+                compressed_size: reader.read_u64()?,
+                size: reader.read_u64()?,
+                checksum: reader.read_u32()?,
+            };
+            raw_entries.push((name, entry));
+        }
+        let index_end = reader.cursor;
+        if checksum(&buf[index_start..index_end]) != reader.read_u32()? {
+            return Err("Asset pack index is corrupted".to_owned());
+        }
+        //names are decoded only after the checksum is verified so damaged index is reported as such
+        let mut entries = HashMap::new();
+        for (name, entry) in raw_entries {
+            let name = String::from_utf8(name.to_vec()).map_err(|e| e.to_string())?;
+            entries.insert(name, entry);
+        }
+        let data_start = reader.cursor;
+        for (name, entry) in &entries {
+            match entry.offset.checked_add(entry.compressed_size) {
+                Some(end) if end <= (buf.len() - data_start) as u64 => {}
+                _ => return Err(format!("Asset pack entry {} is out of bounds", name)),
+            }
+        }
+        Ok(Self {
+            entries,
+            data: buf,
+            data_start,
+        })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(&normalize_name(name))
+    }
+
+    pub fn get_entry(&self, name: &str) -> Option<&PackEntry> {
+        self.entries.get(&normalize_name(name))
+    }
+
+    ///Names of all files stored in the pack
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+
+    ///Decompresses file with the given name and verifies its checksum
+    pub fn read(&self, name: &str) -> Result<Vec<u8>, String> {
+        let entry = self
+            .get_entry(name)
+            .ok_or_else(|| format!("Asset pack has no file named {}", name))?;
+        let start = self.data_start + entry.offset as usize;
+        let end = start + entry.compressed_size as usize;
+        let mut result: Vec<u8> = Vec::with_capacity(entry.size as usize);
+        let decoded = ZlibDecoder::new(&self.data[start..end]).read_to_end(&mut result);
+        if decoded.is_err()
+            || result.len() as u64 != entry.size
+            || checksum(&result) != entry.checksum
+        {
+            return Err(format!("Asset pack file {} is corrupted", name));
+        }
+        Ok(result)
+    }
+
+    ///Reads file from the pack as utf8 string
+    pub fn read_string(&self, name: &str) -> Result<String, String> {
+        String::from_utf8(self.read(name)?).map_err(|e| e.to_string())
+    }
+}
+
+impl AssetPackWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Adds file contents under the given name. Adding same name twice replaces old contents
+    pub fn add(&mut self, name: &str, data: Vec<u8>) {
+        let name = normalize_name(name);
+        self.files.retain(|(file_name, _)| *file_name != name);
+        self.files.push((name, data));
+    }
+
+    ///Reads file from disk and stores it under the given name
+    pub fn add_file(&mut self, name: &str, path: &str) -> Result<(), String> {
+        let mut file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut buf: Vec<u8> = Vec::new();
+        file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+        self.add(name, buf);
+        Ok(())
+    }
+
+    ///Compresses all of the added files and produces pack bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut index: Vec<u8> = Vec::new();
+        let mut blobs: Vec<u8> = Vec::new();
+        index.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        for (name, data) in &self.files {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data).map_err(|e| e.to_string())?;
+            let compressed = encoder.finish().map_err(|e| e.to_string())?;
+            if name.len() > u16::MAX as usize {
+                return Err(format!("File name {} is too long", name));
+            }
+            index.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            index.extend_from_slice(name.as_bytes());
+            index.extend_from_slice(&(blobs.len() as u64).to_le_bytes());
+            index.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+            index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            index.extend_from_slice(&checksum(data).to_le_bytes());
+            blobs.extend_from_slice(&compressed);
+        }
+        let mut result: Vec<u8> = Vec::with_capacity(12 + index.len() + blobs.len());
+        result.extend_from_slice(PACK_MAGIC);
+        result.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        result.extend_from_slice(&index);
+        result.extend_from_slice(&checksum(&index).to_le_bytes());
+        result.extend_from_slice(&blobs);
+        Ok(result)
+    }
+
+    pub fn write_to_file(&self, path: &str) -> Result<(), String> {
+        let mut file = File::create(path).map_err(|e| e.to_string())?;
+        file.write_all(&self.to_bytes()?)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+///Packs all textures listed in the asset descriptor together with the descriptor itself.
+/// Texture paths are resolved the same way `TextureManager::load_from_descriptor` resolves them.
+///
+/// extra_files: any other files that should be stored in the pack, such as settings
+pub fn pack_descriptor(
+    descriptor_path: &str,
+    extra_files: &[String],
+    output: &str,
+) -> Result<usize, String> {
+    let mut writer = AssetPackWriter::new();
+    let data = std::fs::read(descriptor_path).map_err(|e| format!("{}: {}", descriptor_path, e))?;
+    let assets = serde_json::from_slice::<crate::texture_manager::Assets>(&data)
+        .map_err(|e| e.to_string())?;
+    writer.add(DESCRIPTOR_NAME, data);
+    for asset in &assets.textures {
+        writer.add_file(asset.path.as_str(), asset.path.as_str())?;
+    }
+    for path in extra_files {
+        writer.add_file(path.as_str(), path.as_str())?;
+    }
+    writer.write_to_file(output)?;
+    Ok(writer.files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pack() -> Vec<u8> {
+        let mut writer = AssetPackWriter::new();
+        writer.add("./assets/a.txt", b"first file".to_vec());
+        writer.add("assets/b.txt", vec![7u8; 1000]);
+        writer.to_bytes().unwrap()
+    }
+
+    ///Builds pack with single entry and arbitrary index values, index checksum is valid
+    fn pack_with_entry(offset: u64, compressed_size: u64, blob: &[u8]) -> Vec<u8> {
+        let mut index: Vec<u8> = Vec::new();
+        index.extend_from_slice(&1u32.to_le_bytes());
+        index.extend_from_slice(&1u16.to_le_bytes());
+        index.extend_from_slice(b"a");
+        index.extend_from_slice(&offset.to_le_bytes());
+        index.extend_from_slice(&compressed_size.to_le_bytes());
+        index.extend_from_slice(&0u64.to_le_bytes());
+        index.extend_from_slice(&0u32.to_le_bytes());
+        let mut result: Vec<u8> = Vec::new();
+        result.extend_from_slice(PACK_MAGIC);
+        result.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        result.extend_from_slice(&index);
+        result.extend_from_slice(&checksum(&index).to_le_bytes());
+        result.extend_from_slice(blob);
+        result
+    }
+
+    #[test]
+    fn round_trip() {
+        let pack = AssetPack::from_bytes(sample_pack()).unwrap();
+        assert_eq!(pack.names().count(), 2);
+        assert!(pack.contains("assets/a.txt"));
+        assert!(pack.contains("./assets/b.txt"));
+        assert_eq!(pack.read_string("assets/a.txt").unwrap(), "first file");
+        assert_eq!(pack.read("assets/b.txt").unwrap(), vec![7u8; 1000]);
+        assert!(pack.read("assets/c.txt").is_err());
+    }
+
+    #[test]
+    fn corrupted_blob() {
+        let mut buf = sample_pack();
+        let pack = AssetPack::from_bytes(buf.clone()).unwrap();
+        let entry = pack.get_entry("assets/b.txt").unwrap();
+        //flip a byte inside of the compressed deflate stream, after the zlib header
+        let position = pack.data_start + entry.offset as usize + 4;
+        buf[position] ^= 0xFF;
+        let pack = AssetPack::from_bytes(buf).unwrap();
+        let err = pack.read("assets/b.txt").unwrap_err();
+        assert!(err.contains("corrupted"), "{}", err);
+    }
+
+    #[test]
+    fn corrupted_index() {
+        let mut buf = sample_pack();
+        //first byte of the first file name
+        buf[14] ^= 0xFF;
+        assert_eq!(
+            AssetPack::from_bytes(buf).err().unwrap(),
+            "Asset pack index is corrupted"
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let buf = sample_pack();
+        for len in [0, 3, 10, buf.len() - 1] {
+            assert!(AssetPack::from_bytes(buf[..len].to_vec()).is_err());
+        }
+    }
+
+    #[test]
+    fn overflowing_offset() {
+        let buf = pack_with_entry(u64::MAX, 2, &[0, 0]);
+        let err = AssetPack::from_bytes(buf).err().unwrap();
+        assert!(err.contains("out of bounds"), "{}", err);
+        let buf = pack_with_entry(1, 2, &[0, 0]);
+        assert!(AssetPack::from_bytes(buf).is_err());
+        assert!(AssetPack::from_bytes(pack_with_entry(0, 2, &[0, 0])).is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::components::Position;
+use crate::game::DeltaTime;
+use crate::procedural::lerp_color;
+use nalgebra::Vector2;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+use specs::{Component, Join, Read, ReadStorage, System, VecStorage, WriteStorage};
+
+///Single particle simulated by the emitter. Position is in world coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub position: Vector2<f32>,
+    pub velocity: Vector2<f32>,
+    pub acceleration: Vector2<f32>,
+    ///Time in seconds since particle was created
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    ///Value from 0 to 1 representing how much of the lifetime has passed
+    pub fn progress(&self) -> f32 {
+        if self.lifetime <= 0.0 {
+            1.0
+        } else {
+            (self.age / self.lifetime).min(1.0)
+        }
+    }
+}
+
+///Component that spawns and simulates particles at the entity's `Position`.
+/// Particles are drawn in the layer of the entity's `Renderable`
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct ParticleEmitter {
+    ///Name of the texture used for particles, if not set particles are drawn as colored squares
+    pub texture: Option<String>,
+    ///If false emitter will not spawn new particles but existing ones will live out their lifetime
+    pub emitting: bool,
+    ///Particles spawned per second
+    pub rate: f32,
+    ///Maximum amount of particles alive at the same time
+    pub max_particles: usize,
+    ///Range of the particle lifetime in seconds
+    pub lifetime: (f32, f32),
+    ///Range of the starting velocity in pixels per second
+    pub velocity: (Vector2<f32>, Vector2<f32>),
+    ///Range of the constant acceleration in pixels per second squared
+    pub acceleration: (Vector2<f32>, Vector2<f32>),
+    ///Color at the start and at the end of the particle lifetime
+    pub color: (Color, Color),
+    ///Size in pixels at the start and at the end of the particle lifetime
+    pub size: (f32, f32),
+    pub blend_mode: BlendMode,
+    ///Offset from the entity's position where particles are spawned
+    pub offset: Vector2<i32>,
+    pub particles: Vec<Particle>,
+    ///Particles that will be spawned during next update regardless of the rate
+    pending_burst: usize,
+    ///Fraction of the particle that was not spawned during previous update
+    accumulator: f32,
+    random_state: u32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            texture: None,
+            emitting: true,
+            rate: 10.0,
+            max_particles: 256,
+            lifetime: (1.0, 1.0),
+            velocity: (Vector2::new(-20.0, -20.0), Vector2::new(20.0, 20.0)),
+            acceleration: (Vector2::new(0.0, 0.0), Vector2::new(0.0, 0.0)),
+            color: (Color::WHITE, Color::RGBA(255, 255, 255, 0)),
+            size: (4.0, 4.0),
+            blend_mode: BlendMode::Blend,
+            offset: Vector2::new(0, 0),
+            particles: Vec::new(),
+            pending_burst: 0,
+            accumulator: 0.0,
+            random_state: 0x9e37_79b9,
+        }
+    }
+}
+
+impl ParticleEmitter {
+    ///Sets seed used for randomizing particle properties
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        //xorshift gets stuck on zero
+        self.random_state = seed.max(1);
+        self
+    }
+
+    ///Spawns given amount of particles during next update
+    pub fn burst(&mut self, count: usize) {
+        self.pending_burst += count;
+    }
+
+    ///Returns true if emitter is not emitting and all particles have died
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.pending_burst == 0 && self.particles.is_empty()
+    }
+
+    ///Color of the particle based on its age
+    pub fn particle_color(&self, particle: &Particle) -> Color {
+        lerp_color(self.color.0, self.color.1, particle.progress())
+    }
+
+    ///Size of the particle based on its age
+    pub fn particle_size(&self, particle: &Particle) -> f32 {
+        self.size.0 + (self.size.1 - self.size.0) * particle.progress()
+    }
+
+    fn random(&mut self) -> f32 {
+        self.random_state ^= self.random_state << 13;
+        self.random_state ^= self.random_state >> 17;
+        self.random_state ^= self.random_state << 5;
+        (self.random_state & 0xffffff) as f32 / 0xffffff as f32
+    }
+
+    fn random_range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.random()
+    }
+
+    fn random_vector(&mut self, (min, max): (Vector2<f32>, Vector2<f32>)) -> Vector2<f32> {
+        Vector2::new(
+            self.random_range((min.x, max.x)),
+            self.random_range((min.y, max.y)),
+        )
+    }
+
+    fn spawn(&mut self, origin: Vector2<f32>) {
+        if self.particles.len() >= self.max_particles {
+            return;
+        }
+        let particle = Particle {
+            position: origin,
+            velocity: self.random_vector(self.velocity),
+            acceleration: self.random_vector(self.acceleration),
+            age: 0.0,
+            lifetime: self.random_range(self.lifetime),
+        };
+        self.particles.push(particle);
+    }
+
+    ///Moves particles, removes dead ones and spawns new ones
+    pub fn update(&mut self, origin: Vector2<f32>, delta: f32) {
+        for particle in &mut self.particles {
+            particle.age += delta;
+            particle.velocity += particle.acceleration * delta;
+            particle.position += particle.velocity * delta;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        let origin = origin + self.offset.cast::<f32>();
+        let mut count = std::mem::take(&mut self.pending_burst);
+        if self.emitting {
+            self.accumulator += self.rate * delta;
+            count += self.accumulator.floor() as usize;
+            self.accumulator = self.accumulator.fract();
+        }
+        for _ in 0..count {
+            self.spawn(origin);
+        }
+    }
+}
+
+///System that simulates all particle emitters using `DeltaTime` resource
+pub struct ParticleSystem;
+
+impl<'a> System<'a> for ParticleSystem {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, ParticleEmitter>,
+        Read<'a, DeltaTime>,
+    );
+
+    fn run(&mut self, (pos, mut emitter, delta): Self::SystemData) {
+        for (pos, emitter) in (&pos, &mut emitter).join() {
+            emitter.update(Vector2::new(pos.x as f32, pos.y as f32), delta.seconds);
+        }
+    }
+}
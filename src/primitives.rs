@@ -0,0 +1,223 @@
+use crate::render::Canvas;
+use nalgebra::Vector2;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+///Horizontal spans that make up rectangle with rounded corners.
+/// Returns start and end(exclusive) of the span for every row
+fn rounded_rect_spans(width: i32, height: i32, radius: i32) -> Vec<(i32, i32)> {
+    if width <= 0 || height <= 0 {
+        return Vec::new();
+    }
+    let radius = radius.clamp(0, width.min(height) / 2) as f32;
+    (0..height)
+        .map(|row| {
+            let center = row as f32 + 0.5;
+            let dy = if center < radius {
+                radius - center
+            } else if center > height as f32 - radius {
+                center - (height as f32 - radius)
+            } else {
+                0.0
+            };
+            let inset = if dy > 0.0 {
+                (radius - (radius * radius - dy * dy).max(0.0).sqrt()).round() as i32
+            } else {
+                0
+            };
+            (inset, width - inset)
+        })
+        .collect()
+}
+
+///Fills rectangle with rounded corners
+pub fn fill_rounded_rect(
+    canvas: &mut Canvas,
+    area: Rect,
+    radius: i32,
+    color: Color,
+) -> Result<(), String> {
+    let rects: Vec<Rect> = rounded_rect_spans(area.width() as i32, area.height() as i32, radius)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (start, end))| end > start)
+        .map(|(row, (start, end))| {
+            Rect::new(
+                area.x() + start,
+                area.y() + row as i32,
+                (end - start) as u32,
+                1,
+            )
+        })
+        .collect();
+    canvas.set_draw_color(color);
+    canvas.fill_rects(&rects)
+}
+
+///Draws border of the rectangle with rounded corners that goes inwards from the edge
+pub fn draw_rounded_rect(
+    canvas: &mut Canvas,
+    area: Rect,
+    radius: i32,
+    thickness: u32,
+    color: Color,
+) -> Result<(), String> {
+    let (width, height) = (area.width() as i32, area.height() as i32);
+    let thickness = thickness.max(1) as i32;
+    let outer = rounded_rect_spans(width, height, radius);
+    let inner = rounded_rect_spans(
+        width - thickness * 2,
+        height - thickness * 2,
+        (radius - thickness).max(0),
+    );
+    let mut rects: Vec<Rect> = Vec::new();
+    let mut push = |row: i32, start: i32, end: i32| {
+        if end > start {
+            rects.push(Rect::new(
+                area.x() + start,
+                area.y() + row,
+                (end - start) as u32,
+                1,
+            ));
+        }
+    };
+    for (row, (start, end)) in outer.into_iter().enumerate() {
+        let row = row as i32;
+        match inner.get((row - thickness) as usize) {
+            Some((inner_start, inner_end)) if row >= thickness && inner_end > inner_start => {
+                push(row, start, inner_start + thickness);
+                push(row, inner_end + thickness, end);
+            }
+            _ => push(row, start, end),
+        }
+    }
+    canvas.set_draw_color(color);
+    canvas.fill_rects(&rects)
+}
+
+///Fills circle with the given center
+pub fn fill_circle(
+    canvas: &mut Canvas,
+    center: Vector2<i32>,
+    radius: u32,
+    color: Color,
+) -> Result<(), String> {
+    let size = radius * 2;
+    fill_rounded_rect(
+        canvas,
+        Rect::new(
+            center.x - radius as i32,
+            center.y - radius as i32,
+            size,
+            size,
+        ),
+        radius as i32,
+        color,
+    )
+}
+
+///Draws outline of the circle that goes inwards from the radius
+pub fn draw_circle(
+    canvas: &mut Canvas,
+    center: Vector2<i32>,
+    radius: u32,
+    thickness: u32,
+    color: Color,
+) -> Result<(), String> {
+    let size = radius * 2;
+    draw_rounded_rect(
+        canvas,
+        Rect::new(
+            center.x - radius as i32,
+            center.y - radius as i32,
+            size,
+            size,
+        ),
+        radius as i32,
+        thickness,
+        color,
+    )
+}
+
+///Fills polygon using even-odd rule, so self intersecting polygons will have holes
+pub fn fill_polygon(
+    canvas: &mut Canvas,
+    points: &[Vector2<i32>],
+    color: Color,
+) -> Result<(), String> {
+    if points.len() < 3 {
+        return Ok(());
+    }
+    let top = points.iter().map(|p| p.y).min().unwrap_or_default();
+    let bottom = points.iter().map(|p| p.y).max().unwrap_or_default();
+    let mut rects: Vec<Rect> = Vec::new();
+    let mut crossings: Vec<i32> = Vec::new();
+    for y in top..bottom {
+        //sample in the middle of the row to avoid counting vertices twice
+        let sample = y as f32 + 0.5;
+        crossings.clear();
+        for i in 0..points.len() {
+            let a = points[i];
+            let b = points[(i + 1) % points.len()];
+            let (a_y, b_y) = (a.y as f32, b.y as f32);
+            if (a_y <= sample && b_y > sample) || (b_y <= sample && a_y > sample) {
+                let t = (sample - a_y) / (b_y - a_y);
+                crossings.push((a.x as f32 + (b.x - a.x) as f32 * t).round() as i32);
+            }
+        }
+        crossings.sort_unstable();
+        for pair in crossings.chunks_exact(2) {
+            if pair[1] > pair[0] {
+                rects.push(Rect::new(pair[0], y, (pair[1] - pair[0]) as u32, 1));
+            }
+        }
+    }
+    canvas.set_draw_color(color);
+    canvas.fill_rects(&rects)
+}
+
+///Draws line with given thickness. Thick lines are drawn as filled quads
+pub fn draw_line(
+    canvas: &mut Canvas,
+    start: Vector2<i32>,
+    end: Vector2<i32>,
+    thickness: u32,
+    color: Color,
+) -> Result<(), String> {
+    if thickness <= 1 {
+        canvas.set_draw_color(color);
+        return canvas.draw_line((start.x, start.y), (end.x, end.y));
+    }
+    let direction = (end - start).cast::<f32>();
+    let length = direction.norm();
+    if length == 0.0 {
+        return fill_circle(canvas, start, thickness / 2, color);
+    }
+    let half = thickness as f32 / 2.0;
+    let normal = Vector2::new(-direction.y, direction.x) / length * half;
+    let offset = Vector2::new(normal.x.round() as i32, normal.y.round() as i32);
+    fill_polygon(
+        canvas,
+        &[start + offset, end + offset, end - offset, start - offset],
+        color,
+    )
+}
+
+///Draws outline of the closed polygon
+pub fn draw_polygon(
+    canvas: &mut Canvas,
+    points: &[Vector2<i32>],
+    thickness: u32,
+    color: Color,
+) -> Result<(), String> {
+    for i in 0..points.len() {
+        draw_line(
+            canvas,
+            points[i],
+            points[(i + 1) % points.len()],
+            thickness,
+            color,
+        )?;
+    }
+    Ok(())
+}
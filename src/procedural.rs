@@ -0,0 +1,125 @@
+use sdl2::pixels::Color;
+
+///Direction in which gradient changes from first color to second one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientDirection {
+    ///From left to right
+    Horizontal,
+    ///From top to bottom
+    Vertical,
+    ///From top left to bottom right corner
+    Diagonal,
+}
+
+///Fills RGBA32 buffer by calling generator for every pixel
+pub fn fill_pixels<F: FnMut(u32, u32) -> Color>(
+    buffer: &mut [u8],
+    pitch: usize,
+    width: u32,
+    height: u32,
+    mut generator: F,
+) {
+    for y in 0..height {
+        for x in 0..width {
+            let color = generator(x, y);
+            let offset = y as usize * pitch + x as usize * 4;
+            buffer[offset] = color.r;
+            buffer[offset + 1] = color.g;
+            buffer[offset + 2] = color.b;
+            buffer[offset + 3] = color.a;
+        }
+    }
+}
+
+///Size in bytes of tightly packed RGBA32 buffer, fails if it doesn't fit into memory
+pub fn pixel_buffer_len(width: u32, height: u32) -> Result<usize, String> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .ok_or_else(|| format!("Texture size {}x{} is too big", width, height))
+}
+
+///Creates tightly packed RGBA32 pixel buffer using the generator
+pub fn generate_pixels<F: FnMut(u32, u32) -> Color>(
+    width: u32,
+    height: u32,
+    generator: F,
+) -> Result<Vec<u8>, String> {
+    let mut buffer = vec![0u8; pixel_buffer_len(width, height)?];
+    fill_pixels(&mut buffer, width as usize * 4, width, height, generator);
+    Ok(buffer)
+}
+
+///Linear interpolation between two colors, including alpha
+pub fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::RGBA(
+        lerp(from.r, to.r),
+        lerp(from.g, to.g),
+        lerp(from.b, to.b),
+        lerp(from.a, to.a),
+    )
+}
+
+///Checkerboard pattern made of squares with the given size
+pub fn checkerboard(x: u32, y: u32, cell_size: u32, first: Color, second: Color) -> Color {
+    if ((x / cell_size.max(1)) + (y / cell_size.max(1))) & 1 == 0 {
+        first
+    } else {
+        second
+    }
+}
+
+///Color of the gradient at the given pixel
+pub fn gradient(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    from: Color,
+    to: Color,
+    direction: GradientDirection,
+) -> Color {
+    let fraction = |value: u32, size: u32| {
+        if size <= 1 {
+            0.0
+        } else {
+            value as f32 / (size - 1) as f32
+        }
+    };
+    let t = match direction {
+        GradientDirection::Horizontal => fraction(x, width),
+        GradientDirection::Vertical => fraction(y, height),
+        GradientDirection::Diagonal => (fraction(x, width) + fraction(y, height)) * 0.5,
+    };
+    lerp_color(from, to, t)
+}
+
+///Pseudo random value from 0 to 1 for the given lattice point
+fn hash(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32)
+        .wrapping_mul(374_761_393)
+        .wrapping_add((y as u32).wrapping_mul(668_265_263))
+        .wrapping_add(seed.wrapping_mul(2_246_822_519));
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+    (h & 0xffff) as f32 / 65535.0
+}
+
+///Smoothly interpolated value noise in range from 0 to 1
+///
+/// cell_size: distance in pixels between random values, bigger values produce smoother noise
+pub fn value_noise(x: u32, y: u32, cell_size: u32, seed: u32) -> f32 {
+    let cell_size = cell_size.max(1) as f32;
+    let fx = x as f32 / cell_size;
+    let fy = y as f32 / cell_size;
+    let (x0, y0) = (fx.floor() as i32, fy.floor() as i32);
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let tx = smooth(fx - x0 as f32);
+    let ty = smooth(fy - y0 as f32);
+    let top = hash(x0, y0, seed) + (hash(x0 + 1, y0, seed) - hash(x0, y0, seed)) * tx;
+    let bottom =
+        hash(x0, y0 + 1, seed) + (hash(x0 + 1, y0 + 1, seed) - hash(x0, y0 + 1, seed)) * tx;
+    top + (bottom - top) * ty
+}
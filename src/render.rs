@@ -1,7 +1,14 @@
+use crate::anchor::ScreenSize;
 use crate::components::*;
+use crate::effects::ScreenEffects;
 use crate::game::Game;
+use crate::particles::{Particle, ParticleEmitter};
+use crate::primitives;
+use crate::screenshot::FrameCapture;
 use crate::texture_manager::TextureManager;
-use specs::{ReadStorage, World};
+use crate::tilemap::Tilemap;
+use nalgebra::Vector2;
+use specs::{ReadStorage, World, WorldExt};
 
 pub type Canvas = sdl2::render::Canvas<sdl2::video::Window>;
 
@@ -10,6 +17,8 @@ pub type TexturedRenderData<'a> = (
     ReadStorage<'a, Position>,
     ReadStorage<'a, Sprite>,
     ReadStorage<'a, Renderable>,
+    ReadStorage<'a, Rectangle>,
+    ReadStorage<'a, NineSlice>,
 );
 ///Data used for rendering text
 pub type TextRender<'a> = (
@@ -18,19 +27,65 @@ pub type TextRender<'a> = (
     ReadStorage<'a, Renderable>,
 );
 
+///Checks if entity is visible, in one of the active layers and not fully transparent
+fn is_drawn(rend: &Renderable, game: &Game) -> bool {
+    rend.is_visible() && game.draws_layer(rend.layer) && rend.effective_opacity() > 0.0
+}
+
+///Applies blend mode and opacity of the entity to the texture
+fn apply_texture_style(texture: &mut sdl2::render::Texture, rend: &Renderable) {
+    texture.set_blend_mode(rend.blend_mode);
+    texture.set_alpha_mod((rend.effective_opacity() * 255.0).round() as u8);
+}
+
+///Restores default blending of the texture that is shared between entities
+fn reset_texture_style(texture: &mut sdl2::render::Texture) {
+    texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+    texture.set_alpha_mod(255);
+}
+
 ///Function that only renders textures
-/// This relies on texture already being loaded into texture manager
+/// This relies on texture already being loaded into texture manager,
+/// missing textures are handled according to `TextureManager::missing_texture_mode`.
+/// Nine slice panels are drawn first so that sprites can be placed on top of them
 pub fn render_sprites(
     canvas: &mut Canvas,
-    texture_manager: &TextureManager,
-    (pos, sprite, renderable): TexturedRenderData,
+    texture_manager: &mut TextureManager,
+    (pos, sprite, renderable, rect, nine_slice): TexturedRenderData,
     game: &mut Game,
 ) -> Result<(), String> {
     use specs::Join;
+    for (pos, rect, slice, rend) in (&pos, &rect, &nine_slice, &renderable).join() {
+        if !slice.visible || !is_drawn(rend, game) {
+            continue;
+        }
+        let camera = game.camera_for(rend.layer);
+        let (texture, source) = texture_manager.resolve_mut(slice.name.as_str())?;
+        apply_texture_style(texture, rend);
+        let result = render_nine_slice(
+            canvas,
+            texture,
+            source,
+            slice,
+            sdl2::rect::Rect::new(
+                pos.x - camera.x,
+                pos.y - camera.y,
+                rect.width.max(0) as u32,
+                rect.height.max(0) as u32,
+            ),
+        );
+        reset_texture_style(texture);
+        result?;
+    }
     for (pos, sprite, rend) in (&pos, &sprite, &renderable).join() {
-        if !sprite.visible || !rend.visible || (game.active_layers & rend.layer == 0) {
+        //target texture is taken out of the manager while it is drawn into
+        if !sprite.visible
+            || !is_drawn(rend, game)
+            || game.render_target.as_deref() == Some(sprite.name.as_str())
+        {
             continue;
         }
+        let camera = game.camera_for(rend.layer);
         let mut src_rect: Option<sdl2::rect::Rect> = None;
 
         if let Some(src) = sprite.source_rect {
@@ -42,26 +97,212 @@ pub fn render_sprites(
             ));
         }
 
-        if let Some(desc) = texture_manager.get(sprite.name.as_str()) {
-            canvas.copy(
-                texture_manager
-                    .get_raw(desc.source_name.as_str())
-                    .unwrap_or_else(|| &texture_manager.error_texture),
-                sdl2::rect::Rect::new(
-                    desc.source_rect.x as i32,
-                    desc.source_rect.y as i32,
-                    desc.source_rect.z,
-                    desc.source_rect.w,
-                ),
-                sdl2::rect::Rect::new(pos.x, pos.y, sprite.size.x, sprite.size.y),
-            )?;
-        } else {
+        let (texture, source) = texture_manager.resolve_mut(sprite.name.as_str())?;
+        apply_texture_style(texture, rend);
+        let result = canvas.copy(
+            texture,
+            source,
+            sdl2::rect::Rect::new(
+                pos.x - camera.x,
+                pos.y - camera.y,
+                sprite.size.x,
+                sprite.size.y,
+            ),
+        );
+        reset_texture_style(texture);
+        result?;
+    }
+    Ok(())
+}
+
+///Splits length into three parts where first and last have given size.
+/// If length is too small borders are shrunk proportionally
+fn split_length(length: u32, start: u32, end: u32) -> [u32; 3] {
+    if start + end <= length {
+        return [start, length - start - end, end];
+    }
+    let start = (start as u64 * length as u64 / (start + end) as u64) as u32;
+    [start, 0, length - start]
+}
+
+///Copies part of the texture repeating it to cover whole destination, last pieces are cropped
+fn copy_tiled(
+    canvas: &mut Canvas,
+    texture: &sdl2::render::Texture,
+    src: sdl2::rect::Rect,
+    dst: sdl2::rect::Rect,
+) -> Result<(), String> {
+    let mut y = 0;
+    while y < dst.height() {
+        let height = src.height().min(dst.height() - y);
+        let mut x = 0;
+        while x < dst.width() {
+            let width = src.width().min(dst.width() - x);
             canvas.copy(
-                &texture_manager.error_texture,
-                None,
-                sdl2::rect::Rect::new(pos.x, pos.y, sprite.size.x, sprite.size.y),
+                texture,
+                sdl2::rect::Rect::new(src.x(), src.y(), width, height),
+                sdl2::rect::Rect::new(dst.x() + x as i32, dst.y() + y as i32, width, height),
             )?;
+            x += width;
         }
+        y += height;
+    }
+    Ok(())
+}
+
+///Draws nine slice panel into given rectangle
+fn render_nine_slice(
+    canvas: &mut Canvas,
+    texture: &sdl2::render::Texture,
+    source: Option<sdl2::rect::Rect>,
+    slice: &NineSlice,
+    dst: sdl2::rect::Rect,
+) -> Result<(), String> {
+    let source = source.unwrap_or_else(|| {
+        let query = texture.query();
+        sdl2::rect::Rect::new(0, 0, query.width, query.height)
+    });
+    let src_columns = split_length(source.width(), slice.insets.x, slice.insets.z);
+    let src_rows = split_length(source.height(), slice.insets.y, slice.insets.w);
+    let dst_columns = split_length(dst.width(), src_columns[0], src_columns[2]);
+    let dst_rows = split_length(dst.height(), src_rows[0], src_rows[2]);
+
+    let (mut src_y, mut dst_y) = (source.y(), dst.y());
+    for row in 0..3 {
+        let (mut src_x, mut dst_x) = (source.x(), dst.x());
+        for column in 0..3 {
+            if src_columns[column] > 0
+                && src_rows[row] > 0
+                && dst_columns[column] > 0
+                && dst_rows[row] > 0
+            {
+                let src = sdl2::rect::Rect::new(src_x, src_y, src_columns[column], src_rows[row]);
+                let dst =
+                    sdl2::rect::Rect::new(dst_x, dst_y, dst_columns[column], dst_rows[row]);
+                //corners are always drawn as is, everything else is either stretched or tiled
+                if slice.tiled && (row == 1 || column == 1) {
+                    copy_tiled(canvas, texture, src, dst)?;
+                } else {
+                    canvas.copy(texture, src, dst)?;
+                }
+            }
+            src_x += src_columns[column] as i32;
+            dst_x += dst_columns[column] as i32;
+        }
+        src_y += src_rows[row] as i32;
+        dst_y += dst_rows[row] as i32;
+    }
+    Ok(())
+}
+
+///Size of the area that is visible on the current render target
+pub fn view_size(canvas: &Canvas) -> Result<(u32, u32), String> {
+    let logical = canvas.logical_size();
+    if logical.0 > 0 && logical.1 > 0 {
+        return Ok(logical);
+    }
+    let (width, height) = canvas.output_size()?;
+    let (scale_x, scale_y) = canvas.scale();
+    Ok((
+        (width as f32 / scale_x).round() as u32,
+        (height as f32 / scale_y).round() as u32,
+    ))
+}
+
+///Converts point in window coordinates into logical coordinates used for drawing.
+/// SDL already converts mouse events, this is meant for values like `EventPump::mouse_state`
+pub fn window_to_logical(canvas: &Canvas, x: i32, y: i32) -> (i32, i32) {
+    //window size and output size differ on high dpi displays
+    let (window_width, window_height) = canvas.window().size();
+    let (output_width, output_height) = canvas
+        .output_size()
+        .unwrap_or((window_width, window_height));
+    let (scale_x, scale_y) = canvas.scale();
+    let viewport = canvas.viewport();
+    let pixel_x = x as f32 * output_width as f32 / window_width.max(1) as f32;
+    let pixel_y = y as f32 * output_height as f32 / window_height.max(1) as f32;
+    (
+        (pixel_x / scale_x).floor() as i32 - viewport.x(),
+        (pixel_y / scale_y).floor() as i32 - viewport.y(),
+    )
+}
+
+///Draws all tilemaps, only tiles that are visible on the screen are drawn
+pub fn render_tilemaps<'a>(
+    canvas: &mut Canvas,
+    texture_manager: &mut TextureManager,
+    (pos, tilemap, renderable): (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Tilemap>,
+        ReadStorage<'a, Renderable>,
+    ),
+    game: &mut Game,
+) -> Result<(), String> {
+    use specs::Join;
+    let (view_width, view_height) = view_size(canvas)?;
+    //join order depends on entity ids which are reused, so it can't be used as draw order
+    let mut maps: Vec<(&Position, &Tilemap, &Renderable)> =
+        (&pos, &tilemap, &renderable).join().collect();
+    maps.sort_by_key(|(_, map, _)| map.order);
+    for (pos, map, rend) in maps {
+        if !map.visible || !is_drawn(rend, game) {
+            continue;
+        }
+        let tile_width = map.tile_size.x.max(1) as i32;
+        let tile_height = map.tile_size.y.max(1) as i32;
+        //visible area relative to the map's origin
+        let camera = game.camera_for(rend.layer);
+        let left = camera.x - pos.x;
+        let top = camera.y - pos.y;
+        let first_column = (left / tile_width).max(0);
+        let first_row = (top / tile_height).max(0);
+        let last_column = ((left + view_width as i32) / tile_width + 1).min(map.width() as i32);
+        let last_row = ((top + view_height as i32) / tile_height + 1).min(map.height() as i32);
+        if first_column >= last_column || first_row >= last_row {
+            continue;
+        }
+
+        let (texture, source) = texture_manager.resolve_mut(map.tileset.as_str())?;
+        apply_texture_style(texture, rend);
+        let source = source.unwrap_or_else(|| {
+            let query = texture.query();
+            sdl2::rect::Rect::new(0, 0, query.width, query.height)
+        });
+        let columns = (source.width() / map.tile_size.x.max(1)).max(1);
+        for layer in map.layers.iter().filter(|layer| layer.visible) {
+            for y in first_row..last_row {
+                for x in first_column..last_column {
+                    let Some(tile) = map.get_tile_in(layer, x as u32, y as u32) else {
+                        continue;
+                    };
+                    let (angle, flip_horizontal, flip_vertical) = tile.transform();
+                    let result = canvas.copy_ex(
+                        texture,
+                        sdl2::rect::Rect::new(
+                            source.x() + ((tile.id % columns) * map.tile_size.x) as i32,
+                            source.y() + ((tile.id / columns) * map.tile_size.y) as i32,
+                            map.tile_size.x,
+                            map.tile_size.y,
+                        ),
+                        sdl2::rect::Rect::new(
+                            pos.x + x * tile_width - camera.x,
+                            pos.y + y * tile_height - camera.y,
+                            map.tile_size.x,
+                            map.tile_size.y,
+                        ),
+                        angle,
+                        None,
+                        flip_horizontal,
+                        flip_vertical,
+                    );
+                    if let Err(err) = result {
+                        reset_texture_style(texture);
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        reset_texture_style(texture);
     }
     Ok(())
 }
@@ -80,12 +321,13 @@ pub fn render_rect<'a>(
     use specs::Join;
     canvas.set_draw_color(sdl2::pixels::Color::RGB(255, 0, 0));
     for (pos, rect, rend) in (&pos, &rect, &renderable).join() {
-        if !rend.visible || (game.active_layers & rend.layer == 0) {
+        if !rend.is_visible() || !game.draws_layer(rend.layer) {
             continue;
         }
+        let camera = game.camera_for(rend.layer);
         canvas.draw_rect(sdl2::rect::Rect::new(
-            pos.x,
-            pos.y,
+            pos.x - camera.x,
+            pos.y - camera.y,
             rect.width as u32,
             rect.height as u32,
         ))?;
@@ -107,17 +349,165 @@ pub fn render_fill<'a>(
 ) -> Result<(), String> {
     use specs::Join;
     for (pos, rect, col, rend) in (&pos, &rect, &col, &renderable).join() {
-        if !rend.visible || (game.active_layers & rend.layer == 0) {
+        if !is_drawn(rend, game) {
             continue;
         }
-        canvas.set_draw_color(col.color);
+        canvas.set_blend_mode(rend.blend_mode);
+        let camera = game.camera_for(rend.layer);
+        canvas.set_draw_color(rend.apply_opacity(col.color));
         canvas.fill_rect(sdl2::rect::Rect::new(
-            pos.x,
-            pos.y,
+            pos.x - camera.x,
+            pos.y - camera.y,
             rect.width as u32,
             rect.height as u32,
         ))?;
     }
+    canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+    Ok(())
+}
+
+///Data used for rendering shapes
+pub type ShapeRenderData<'a> = (
+    ReadStorage<'a, Position>,
+    ReadStorage<'a, Rectangle>,
+    ReadStorage<'a, Line>,
+    ReadStorage<'a, Circle>,
+    ReadStorage<'a, Polygon>,
+    ReadStorage<'a, RoundedRect>,
+    ReadStorage<'a, Renderable>,
+);
+
+///Draws all lines, circles, polygons and rounded rectangles.
+/// Fill is drawn first and outline is drawn on top of it
+pub fn render_shapes(
+    canvas: &mut Canvas,
+    (pos, rect, line, circle, polygon, rounded, renderable): ShapeRenderData,
+    game: &mut Game,
+) -> Result<(), String> {
+    use specs::Join;
+    for (pos, line, rend) in (&pos, &line, &renderable).join() {
+        if !is_drawn(rend, game) {
+            continue;
+        }
+        canvas.set_blend_mode(rend.blend_mode);
+        let camera = game.camera_for(rend.layer);
+        let start = Vector2::new(pos.x, pos.y) - camera;
+        primitives::draw_line(
+            canvas,
+            start,
+            start + line.end,
+            line.thickness,
+            rend.apply_opacity(line.color),
+        )?;
+    }
+    for (pos, circle, rend) in (&pos, &circle, &renderable).join() {
+        if !is_drawn(rend, game) {
+            continue;
+        }
+        canvas.set_blend_mode(rend.blend_mode);
+        let camera = game.camera_for(rend.layer);
+        let center = Vector2::new(pos.x, pos.y) - camera;
+        if let Some(fill) = circle.style.fill.map(|c| rend.apply_opacity(c)) {
+            primitives::fill_circle(canvas, center, circle.radius, fill)?;
+        }
+        if let Some(outline) = circle.style.outline.map(|c| rend.apply_opacity(c)) {
+            primitives::draw_circle(canvas, center, circle.radius, circle.style.thickness, outline)?;
+        }
+    }
+    for (pos, polygon, rend) in (&pos, &polygon, &renderable).join() {
+        if !is_drawn(rend, game) {
+            continue;
+        }
+        canvas.set_blend_mode(rend.blend_mode);
+        let camera = game.camera_for(rend.layer);
+        let origin = Vector2::new(pos.x, pos.y) - camera;
+        let points: Vec<Vector2<i32>> = polygon.points.iter().map(|p| origin + p).collect();
+        if let Some(fill) = polygon.style.fill.map(|c| rend.apply_opacity(c)) {
+            primitives::fill_polygon(canvas, &points, fill)?;
+        }
+        if let Some(outline) = polygon.style.outline.map(|c| rend.apply_opacity(c)) {
+            primitives::draw_polygon(canvas, &points, polygon.style.thickness, outline)?;
+        }
+    }
+    for (pos, rect, rounded, rend) in (&pos, &rect, &rounded, &renderable).join() {
+        if !is_drawn(rend, game) {
+            continue;
+        }
+        canvas.set_blend_mode(rend.blend_mode);
+        let camera = game.camera_for(rend.layer);
+        let area = sdl2::rect::Rect::new(
+            pos.x - camera.x,
+            pos.y - camera.y,
+            rect.width.max(0) as u32,
+            rect.height.max(0) as u32,
+        );
+        if let Some(fill) = rounded.style.fill.map(|c| rend.apply_opacity(c)) {
+            primitives::fill_rounded_rect(canvas, area, rounded.radius as i32, fill)?;
+        }
+        if let Some(outline) = rounded.style.outline.map(|c| rend.apply_opacity(c)) {
+            primitives::draw_rounded_rect(
+                canvas,
+                area,
+                rounded.radius as i32,
+                rounded.style.thickness,
+                outline,
+            )?;
+        }
+    }
+    canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+    Ok(())
+}
+
+///Draws particles of all emitters.
+/// Textured particles are tinted using texture color and alpha modulation
+pub fn render_particles<'a>(
+    canvas: &mut Canvas,
+    texture_manager: &mut TextureManager,
+    (emitter, renderable): (
+        ReadStorage<'a, ParticleEmitter>,
+        ReadStorage<'a, Renderable>,
+    ),
+    game: &mut Game,
+) -> Result<(), String> {
+    use specs::Join;
+    for (emitter, rend) in (&emitter, &renderable).join() {
+        if !is_drawn(rend, game) || emitter.particles.is_empty() {
+            continue;
+        }
+        let camera = game.camera_for(rend.layer);
+        let dest = |particle: &Particle| {
+            let size = emitter.particle_size(particle).max(0.0);
+            sdl2::rect::Rect::new(
+                (particle.position.x - size / 2.0) as i32 - camera.x,
+                (particle.position.y - size / 2.0) as i32 - camera.y,
+                size.round() as u32,
+                size.round() as u32,
+            )
+        };
+        match &emitter.texture {
+            Some(name) => {
+                let (texture, source) = texture_manager.resolve_mut(name.as_str())?;
+                texture.set_blend_mode(emitter.blend_mode);
+                for particle in &emitter.particles {
+                    let color = rend.apply_opacity(emitter.particle_color(particle));
+                    texture.set_color_mod(color.r, color.g, color.b);
+                    texture.set_alpha_mod(color.a);
+                    canvas.copy(texture, source, dest(particle))?;
+                }
+                texture.set_color_mod(255, 255, 255);
+                texture.set_alpha_mod(255);
+                texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+            }
+            None => {
+                canvas.set_blend_mode(emitter.blend_mode);
+                for particle in &emitter.particles {
+                    canvas.set_draw_color(rend.apply_opacity(emitter.particle_color(particle)));
+                    canvas.fill_rect(dest(particle))?;
+                }
+                canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+            }
+        }
+    }
     Ok(())
 }
 
@@ -132,23 +522,25 @@ pub fn render_text(
 ) -> Result<(), String> {
     use specs::Join;
     for (pos, text, rend) in (&pos, &text, &renderable).join() {
-        if !text.visible || !rend.visible || (game.active_layers & rend.layer == 0) {
+        if !text.visible || !is_drawn(rend, game) {
             continue;
         }
         let surface = font
             .render(text.text.as_str())
             .blended(text.color)
             .map_err(|e| e.to_string())?;
-        let texture = texture_creator
+        let mut texture = texture_creator
             .create_texture_from_surface(&surface)
             .map_err(|e| e.to_string())?;
+        apply_texture_style(&mut texture, rend);
         let query = texture.query();
+        let camera = game.camera_for(rend.layer);
         canvas.copy(
             &texture,
             None,
             sdl2::rect::Rect::new(
-                pos.x + text.offset.x,
-                pos.y + text.offset.y,
+                pos.x + text.offset.x - camera.x,
+                pos.y + text.offset.y - camera.y,
                 query.width,
                 query.height,
             ),
@@ -157,25 +549,157 @@ pub fn render_text(
     Ok(())
 }
 
+///Offscreen texture that part of the world can be rendered into.
+/// Texture is registered in the texture manager so it can be drawn using ordinary `Sprite`
+pub struct RenderTarget {
+    ///Name under which texture is stored in the texture manager
+    pub name: String,
+    pub size: Vector2<u32>,
+    ///Position of the top left corner of the rendered area in the world
+    pub camera: Vector2<i32>,
+    ///Layers that will be drawn into the target
+    pub layers: u32,
+    pub clear_color: sdl2::pixels::Color,
+}
+
+impl RenderTarget {
+    pub fn new(name: String, size: Vector2<u32>, layers: u32) -> Self {
+        Self {
+            name,
+            size,
+            camera: Vector2::new(0, 0),
+            layers,
+            clear_color: sdl2::pixels::Color::RGBA(0, 0, 0, 0),
+        }
+    }
+}
+
+///Runs all of the render passes using current canvas target
+pub fn render_passes(
+    world: &World,
+    canvas: &mut Canvas,
+    textures: &mut TextureManager,
+    game: &mut Game,
+    font: &sdl2::ttf::Font,
+) -> Result<(), String> {
+    use specs::Join;
+    //layers are drawn one by one from the lowest bit so higher layers are always on top.
+    // Entity that is in several layers is drawn once, in its highest active layer
+    let layers = world
+        .read_storage::<Renderable>()
+        .join()
+        .filter_map(|rend| game.top_layer(rend.layer))
+        .fold(0u32, |layers, bit| layers | (1 << bit));
+    for bit in (0..u32::BITS).filter(|bit| layers & (1 << bit) != 0) {
+        let mut view = Game {
+            active_layers: game.active_layers,
+            pass_layer: Some(bit),
+            camera: game.camera,
+            screen_space_layers: game.screen_space_layers,
+            render_target: game.render_target.clone(),
+        };
+        render_tilemaps(canvas, textures, world.system_data(), &mut view)?;
+        render_fill(canvas, world.system_data(), &mut view)?;
+        render_sprites(canvas, textures, world.system_data(), &mut view)?;
+        render_shapes(canvas, world.system_data(), &mut view)?;
+        render_particles(canvas, textures, world.system_data(), &mut view)?;
+        render_text(
+            canvas,
+            font,
+            &canvas.texture_creator(),
+            world.system_data(),
+            &mut view,
+        )?;
+    }
+    Ok(())
+}
+
+///Renders layers selected by the target into its texture, creating texture if needed.
+/// Only layers that are active in the game are drawn
+pub fn render_to_texture(
+    world: &World,
+    canvas: &mut Canvas,
+    textures: &mut TextureManager,
+    target: &RenderTarget,
+    game: &Game,
+    font: &sdl2::ttf::Font,
+) -> Result<(), String> {
+    let mut texture = match textures.take_raw(target.name.as_str()) {
+        Some(texture)
+            if texture.query().width == target.size.x
+                && texture.query().height == target.size.y =>
+        {
+            texture
+        }
+        _ => textures.create_target_texture(target.size)?,
+    };
+    let mut view = Game {
+        active_layers: game.active_layers & target.layers,
+        camera: target.camera,
+        screen_space_layers: game.screen_space_layers,
+        render_target: Some(target.name.clone()),
+        pass_layer: None,
+    };
+    let mut result = Ok(());
+    let canvas_result = canvas.with_texture_canvas(&mut texture, |target_canvas| {
+        target_canvas.set_draw_color(target.clear_color);
+        target_canvas.clear();
+        result = render_passes(world, target_canvas, textures, &mut view, font);
+    });
+    textures.insert_raw(target.name.clone(), texture);
+    canvas_result.map_err(|e| e.to_string())?;
+    result
+}
+
+///Draws letterbox bars, flash and fade on top of the whole screen
+pub fn render_screen_effects(canvas: &mut Canvas, effects: &ScreenEffects) -> Result<(), String> {
+    let (width, height) = view_size(canvas)?;
+    let bar = effects.letterbox_height().min(height / 2);
+    if bar > 0 {
+        canvas.set_draw_color(effects.letterbox_color);
+        canvas.fill_rects(&[
+            sdl2::rect::Rect::new(0, 0, width, bar),
+            sdl2::rect::Rect::new(0, (height - bar) as i32, width, bar),
+        ])?;
+    }
+    for color in [effects.flash_color(), effects.fade_color()] {
+        if color.a > 0 {
+            canvas.set_draw_color(color);
+            canvas.fill_rect(None)?;
+        }
+    }
+    Ok(())
+}
+
 ///Render everything to the screen
 pub fn render_game(
     world: &World,
     canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-    textures: &TextureManager,
+    textures: &mut TextureManager,
     game: &mut Game,
     font: &sdl2::ttf::Font,
 ) -> Result<(), String> {
     canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 0, 0, 255));
     canvas.clear();
-    render_fill(canvas, world.system_data(), game)?;
-    render_sprites(canvas, textures, world.system_data(), game)?;
-    render_text(
-        canvas,
-        font,
-        &canvas.texture_creator(),
-        world.system_data(),
-        game,
-    )?;
+    //anchored UI uses the size on the next update, after window settings were applied
+    if let Some(mut screen) = world.try_fetch_mut::<ScreenSize>() {
+        *screen = ScreenSize::from_canvas(canvas)?;
+    }
+    let effects = world.try_fetch::<ScreenEffects>();
+    let shake = effects
+        .as_ref()
+        .map(|effects| effects.shake_offset())
+        .unwrap_or_else(|| Vector2::new(0, 0));
+    game.camera += shake;
+    let result = render_passes(world, canvas, textures, game, font);
+    game.camera -= shake;
+    result?;
+    if let Some(effects) = effects {
+        render_screen_effects(canvas, &effects)?;
+    }
+    if let Some(mut capture) = world.try_fetch_mut::<FrameCapture>() {
+        capture.capture(canvas)?;
+    }
     canvas.present();
     Ok(())
 }
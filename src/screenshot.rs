@@ -0,0 +1,218 @@
+use crate::asset_loader::DecodedImage;
+use crate::render::Canvas;
+use crate::texture_manager::TextureManager;
+use sdl2::event::Event;
+use sdl2::image::SaveSurface;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use std::path::{Path, PathBuf};
+
+///Reads pixels of the whole window, including letterbox bars.
+/// Must be called after drawing but before the canvas is presented
+pub fn capture_frame(canvas: &mut Canvas) -> Result<DecodedImage, String> {
+    let logical = canvas.logical_size();
+    let scale = canvas.scale();
+    let viewport = canvas.viewport();
+    //logical size and scale limit reading to the viewport so they are disabled while reading
+    canvas.set_logical_size(0, 0).map_err(|e| e.to_string())?;
+    canvas.set_scale(1.0, 1.0)?;
+    canvas.set_viewport(None);
+    let result = canvas.output_size().and_then(|(width, height)| {
+        Ok(DecodedImage {
+            width,
+            height,
+            pitch: width as usize * 4,
+            pixels: canvas.read_pixels(None, PixelFormatEnum::RGBA32)?,
+        })
+    });
+    if logical.0 > 0 && logical.1 > 0 {
+        canvas
+            .set_logical_size(logical.0, logical.1)
+            .map_err(|e| e.to_string())?;
+    } else {
+        canvas.set_scale(scale.0, scale.1)?;
+    }
+    //viewport is in scaled coordinates so it is restored last
+    canvas.set_viewport(viewport);
+    result
+}
+
+///Reads pixels of the texture from the texture manager, for example target created by `render::render_to_texture`.
+/// Texture has to be created with target access
+pub fn capture_texture(
+    canvas: &mut Canvas,
+    textures: &mut TextureManager,
+    name: &str,
+) -> Result<DecodedImage, String> {
+    let (texture, source) = textures.resolve_mut(name)?;
+    let area = source.unwrap_or_else(|| {
+        let query = texture.query();
+        sdl2::rect::Rect::new(0, 0, query.width, query.height)
+    });
+    let mut result = Err(format!("Failed to read pixels of texture {}", name));
+    canvas
+        .with_texture_canvas(texture, |target| {
+            result = target.read_pixels(area, PixelFormatEnum::RGBA32);
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(DecodedImage {
+        width: area.width(),
+        height: area.height(),
+        pitch: area.width() as usize * 4,
+        pixels: result?,
+    })
+}
+
+///Saves RGBA32 image as png file
+pub fn save_image<P: AsRef<Path>>(image: &DecodedImage, path: P) -> Result<(), String> {
+    let mut pixels = image.pixels.clone();
+    let surface = Surface::from_data(
+        &mut pixels,
+        image.width,
+        image.height,
+        image.pitch as u32,
+        PixelFormatEnum::RGBA32,
+    )?;
+    surface.save(path)
+}
+
+///Saves current frame as png file
+pub fn save_screenshot<P: AsRef<Path>>(canvas: &mut Canvas, path: P) -> Result<(), String> {
+    save_image(&capture_frame(canvas)?, path)
+}
+
+///State of the image sequence recording
+#[derive(Clone, Debug, PartialEq)]
+struct Recording {
+    prefix: String,
+    timestep: f32,
+    frame: u32,
+}
+
+///Resource that saves screenshots and records image sequences.
+/// `render::render_game` captures requested frames right before presenting them
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameCapture {
+    ///Folder where screenshots and recorded frames are saved
+    pub directory: PathBuf,
+    ///Key that takes a screenshot when handled by `handle_event`
+    pub screenshot_key: Option<Keycode>,
+    ///Key that starts and stops recording when handled by `handle_event`
+    pub record_key: Option<Keycode>,
+    ///Frame rate used for recordings started with the record key
+    pub record_fps: u32,
+    pending: Vec<PathBuf>,
+    recording: Option<Recording>,
+    screenshot_count: u32,
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("./screenshots"),
+            screenshot_key: Some(Keycode::F12),
+            record_key: None,
+            record_fps: 30,
+            pending: Vec::new(),
+            recording: None,
+            screenshot_count: 0,
+        }
+    }
+}
+
+impl FrameCapture {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    ///Saves next frame to the given path
+    pub fn request_screenshot<P: AsRef<Path>>(&mut self, path: P) {
+        self.pending.push(path.as_ref().to_path_buf());
+    }
+
+    ///Saves next frame to the capture directory using generated name
+    pub fn take_screenshot(&mut self) {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        let path = self
+            .directory
+            .join(format!("screenshot_{}_{}.png", time, self.screenshot_count));
+        self.screenshot_count += 1;
+        self.pending.push(path);
+    }
+
+    ///Starts saving every frame as numbered image, for example `prefix_00001.png`.
+    /// While recording game should use `fixed_delta` as the frame time
+    pub fn start_recording(&mut self, prefix: String, fps: u32) {
+        self.recording = Some(Recording {
+            prefix,
+            timestep: 1.0 / fps.max(1) as f32,
+            frame: 0,
+        });
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    ///Time step that game should simulate per frame while recording
+    pub fn fixed_delta(&self) -> Option<f32> {
+        self.recording.as_ref().map(|recording| recording.timestep)
+    }
+
+    ///Handles screenshot and record hotkeys
+    pub fn handle_event(&mut self, event: &Event) {
+        if let Event::KeyDown {
+            keycode: Some(key),
+            repeat: false,
+            ..
+        } = event
+        {
+            if self.screenshot_key == Some(*key) {
+                self.take_screenshot();
+            } else if self.record_key == Some(*key) {
+                if self.is_recording() {
+                    self.stop_recording();
+                } else {
+                    self.start_recording("frame".to_owned(), self.record_fps);
+                }
+            }
+        }
+    }
+
+    ///Returns true if current frame has to be read back
+    pub fn has_work(&self) -> bool {
+        !self.pending.is_empty() || self.recording.is_some()
+    }
+
+    ///Saves current frame for all pending screenshots and recording
+    pub fn capture(&mut self, canvas: &mut Canvas) -> Result<(), String> {
+        if !self.has_work() {
+            return Ok(());
+        }
+        let image = capture_frame(canvas)?;
+        std::fs::create_dir_all(&self.directory).map_err(|e| e.to_string())?;
+        for path in self.pending.drain(..) {
+            save_image(&image, path)?;
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.frame += 1;
+            save_image(
+                &image,
+                self.directory
+                    .join(format!("{}_{:05}.png", recording.prefix, recording.frame)),
+            )?;
+        }
+        Ok(())
+    }
+}
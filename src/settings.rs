@@ -1,9 +1,69 @@
+use crate::pack::AssetPack;
 use sdl2::video::FullscreenType;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 
+///How logical resolution is fit into the window
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ScalingPolicy {
+    ///Logical resolution fills the whole window, aspect ratio is not preserved
+    Stretch = 0,
+    ///Aspect ratio is preserved and unused space is filled with black bars
+    #[default]
+    Letterbox = 1,
+    ///Only whole number scales are used to keep pixel art sharp, unused space is filled with black bars
+    Integer = 2,
+}
+
+impl ScalingPolicy {
+    ///Converts value stored in settings, unknown values use letterbox
+    pub fn from_value(value: i32) -> Self {
+        match value {
+            0 => Self::Stretch,
+            2 => Self::Integer,
+            _ => Self::Letterbox,
+        }
+    }
+}
+
+///Makes canvas draw everything in the logical resolution scaled to the window using given policy
+pub fn apply_scaling(
+    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+    width: u32,
+    height: u32,
+    policy: ScalingPolicy,
+) -> Result<(), String> {
+    match policy {
+        ScalingPolicy::Stretch => {
+            //logical size always preserves aspect ratio so scale is used directly instead
+            canvas.set_logical_size(0, 0).map_err(|e| e.to_string())?;
+            canvas.set_integer_scale(false)?;
+            let (output_width, output_height) = canvas.output_size()?;
+            canvas.set_scale(
+                output_width as f32 / width.max(1) as f32,
+                output_height as f32 / height.max(1) as f32,
+            )
+        }
+        ScalingPolicy::Letterbox | ScalingPolicy::Integer => {
+            canvas
+                .set_logical_size(width, height)
+                .map_err(|e| e.to_string())?;
+            canvas.set_integer_scale(policy == ScalingPolicy::Integer)
+        }
+    }
+}
+
+///Settings that are used by `Settings::apply_sdl_settings`, changing other settings doesn't reapply window mode
+const SDL_SETTINGS: [&str; 5] = [
+    "fullscreen_type",
+    "resolution",
+    "logical_width",
+    "logical_height",
+    "scaling",
+];
+
 /**Struct that holds info about current player settings */
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Settings {
@@ -29,8 +89,16 @@ impl SettingsInfo {
         file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
         let data = String::from_utf8(buf).map_err(|e| e.to_string())?;
         println!("{}", data);
-        let mut result: SettingsInfo =
-            serde_json::from_str(data.as_str()).map_err(|e| e.to_string())?;
+        Self::from_json(data.as_str())
+    }
+
+    ///Reads settings info stored in the asset pack
+    pub fn from_pack(pack: &AssetPack) -> Result<Self, String> {
+        Self::from_json(pack.read_string("settings_info.json")?.as_str())
+    }
+
+    fn from_json(data: &str) -> Result<Self, String> {
+        let mut result: SettingsInfo = serde_json::from_str(data).map_err(|e| e.to_string())?;
         let mut names: HashMap<i32, String> = HashMap::new();
         /*
             the next piece of code does some string processing to
@@ -86,8 +154,16 @@ impl Settings {
         file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
         let data = String::from_utf8(buf).map_err(|e| e.to_string())?;
         println!("{}", data);
-        let mut result: Settings =
-            serde_json::from_str(data.as_str()).map_err(|e| e.to_string())?;
+        Self::from_json(data.as_str())
+    }
+
+    ///Reads settings stored in the asset pack, useful for shipping default settings
+    pub fn new_from_pack(pack: &AssetPack) -> Result<Self, String> {
+        Self::from_json(pack.read_string("settings.json")?.as_str())
+    }
+
+    fn from_json(data: &str) -> Result<Self, String> {
+        let mut result: Settings = serde_json::from_str(data).map_err(|e| e.to_string())?;
         //set this value to true to force game to apply loaded settings
         result.values_changed = true;
         Ok(result)
@@ -105,9 +181,40 @@ impl Settings {
         self.values.get(&name)
     }
 
+    ///Stores the value. Window settings are reapplied by `apply_sdl_settings` only if one of them has changed
     pub fn write(&mut self, name: String, value: i32) {
-        self.values_changed = true;
-        self.values.insert(name, value);
+        let window_setting = SDL_SETTINGS.contains(&name.as_str());
+        if self.values.insert(name, value) != Some(value) && window_setting {
+            self.values_changed = true;
+        }
+    }
+
+    ///Removes the value, returns old value if there was one
+    pub fn remove(&mut self, name: &str) -> Option<i32> {
+        let result = self.values.remove(name);
+        if result.is_some() && SDL_SETTINGS.contains(&name) {
+            self.values_changed = true;
+        }
+        result
+    }
+
+    ///All stored settings values
+    pub fn values(&self) -> &HashMap<String, i32> {
+        &self.values
+    }
+
+    ///Size of the virtual screen that the game is drawn to
+    pub fn logical_size(&self) -> (u32, u32) {
+        let width = self.values.get("logical_width").copied().unwrap_or(800);
+        let height = self.values.get("logical_height").copied().unwrap_or(600);
+        (width.max(1) as u32, height.max(1) as u32)
+    }
+
+    pub fn scaling_policy(&self) -> ScalingPolicy {
+        self.values
+            .get("scaling")
+            .map(|value| ScalingPolicy::from_value(*value))
+            .unwrap_or_default()
     }
 
     pub fn get_settings<'a>(
@@ -120,6 +227,25 @@ impl Settings {
         }
     }
 
+    ///Keeps scaling up to date when window is resized by the player or the system.
+    /// Stretch scale depends on the window size so it has to be recomputed after every resize
+    pub fn handle_event(
+        &self,
+        event: &sdl2::event::Event,
+        canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+    ) -> Result<(), String> {
+        use sdl2::event::{Event, WindowEvent};
+        if let Event::Window {
+            win_event: WindowEvent::SizeChanged(..),
+            ..
+        } = event
+        {
+            let (width, height) = self.logical_size();
+            apply_scaling(canvas, width, height, self.scaling_policy())?;
+        }
+        Ok(())
+    }
+
     ///applies settings related to window size,resolution etc
     pub fn apply_sdl_settings(
         &mut self,
@@ -138,23 +264,16 @@ impl Settings {
             };
             if let Some(res) = self.values.get("resolution") {
                 let temp = info.resolution_values[*res as usize];
-                if matches!(fullscreen_mode, FullscreenType::Off) {
-                    canvas
-                        .window_mut()
-                        .set_size(temp.0, temp.1)
-                        .map_err(|e| e.to_string())?;
-                    canvas
-                        .set_logical_size(800, 600)
-                        .map_err(|e| e.to_string())?;
-                } else {
-                    canvas
-                        .window_mut()
-                        .set_size(temp.0, temp.1)
-                        .map_err(|e| e.to_string())?;
-                }
+                canvas
+                    .window_mut()
+                    .set_size(temp.0, temp.1)
+                    .map_err(|e| e.to_string())?;
             }
             canvas.window_mut().set_fullscreen(fullscreen_mode)?;
         }
+        //scaling depends on the output size so it has to be applied after window changes
+        let (width, height) = self.logical_size();
+        apply_scaling(canvas, width, height, self.scaling_policy())?;
         self.values_changed = false;
         Ok(())
     }
@@ -168,6 +287,9 @@ impl Default for Settings {
                 //These are default settings that should be shared between projects
                 ("resolution".to_owned(), 0),
                 ("fullscreen".to_owned(), 0),
+                ("logical_width".to_owned(), 800),
+                ("logical_height".to_owned(), 600),
+                ("scaling".to_owned(), ScalingPolicy::Letterbox as i32),
             ]),
         }
     }
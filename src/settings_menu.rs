@@ -0,0 +1,208 @@
+use crate::components::*;
+use crate::layers::RenderLayers;
+use crate::settings::{Settings, SettingsInfo};
+use crate::ui::{make_button_base, make_dropdown_item, make_dropdown_parent, Focusable, UiEvent};
+use nalgebra::Vector2;
+use specs::shrev::EventChannel;
+use specs::{
+    Builder, Component, Entity, Join, Read, ReadStorage, ReaderId, System, VecStorage, World,
+    WorldExt, Write, WriteStorage,
+};
+
+///Copy of the settings that is edited by the settings menu until changes are applied
+#[derive(Clone, Default)]
+pub struct PendingSettings(pub Settings);
+
+///Button of the settings menu that applies, reverts or resets pending settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+#[storage(VecStorage)]
+pub enum SettingsMenuAction {
+    ///Copies pending settings into the `Settings` resource
+    Apply,
+    ///Discards pending changes
+    Revert,
+    ///Replaces pending values with the default settings
+    Defaults,
+}
+
+///Converts setting name like "fullscreen_type" into label like "Fullscreen type"
+fn setting_label(name: &str) -> String {
+    let text = name.replace('_', " ");
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => text,
+    }
+}
+
+///Name of the settings value that is displayed to the player
+fn value_name(info: &SettingsInfo, settings: &Settings, name: &str) -> String {
+    let Some(value) = settings.get(name.to_owned()) else {
+        return "-".to_owned();
+    };
+    info.names
+        .get(name)
+        .and_then(|names| names.get(value))
+        .cloned()
+        .unwrap_or_else(|| value.to_string())
+}
+
+///Builds settings screen with one labelled dropdown for every setting in `SettingsInfo` resource
+/// and Apply, Revert and Defaults buttons below them.
+/// Changes are written into `PendingSettings` resource and copied into `Settings` resource when applied,
+/// saving them to the file is left to the game. Returns all created entities, including dropdown items
+pub fn generate_settings_menu(
+    world: &mut World,
+    location: Vector2<i32>,
+    row_size: Vector2<i32>,
+    color: sdl2::pixels::Color,
+    layer: RenderLayers,
+) -> Vec<Entity> {
+    world.register::<SettingsMenuAction>();
+    world.entry::<Settings>().or_insert_with(Settings::default);
+    let info = world
+        .entry::<SettingsInfo>()
+        .or_insert_with(SettingsInfo::default)
+        .clone();
+    let settings = (*world.read_resource::<Settings>()).clone();
+    world.insert(PendingSettings(settings.clone()));
+
+    let mut names: Vec<&String> = info.names.keys().collect();
+    names.sort();
+    let mut result: Vec<Entity> = Vec::new();
+    let mut y = location.y;
+    for name in names {
+        result.push(
+            world
+                .create_entity()
+                .with(Position { x: location.x, y })
+                .with(Text {
+                    text: setting_label(name),
+                    color: sdl2::pixels::Color::WHITE,
+                    visible: true,
+                    offset: Vector2::new(0, 0),
+                })
+                .with(Renderable::new(true, layer as u32))
+                .build(),
+        );
+        let parent = make_dropdown_parent(
+            world,
+            Vector2::new(location.x + row_size.x, y),
+            row_size,
+            value_name(&info, &settings, name),
+            color,
+            layer,
+        )
+        .with(SettingsValueDisplay { name: name.clone() })
+        .build();
+        result.push(parent);
+
+        let mut values: Vec<(&i32, &String)> = info.names[name].iter().collect();
+        values.sort();
+        for (list_id, (value, text)) in values.into_iter().enumerate() {
+            result.push(
+                make_dropdown_item(world, parent, list_id as i32, row_size, text.clone(), color)
+                    .with(SettingsValue {
+                        name: name.clone(),
+                        value: *value,
+                    })
+                    .build(),
+            );
+        }
+        y += row_size.y;
+    }
+
+    let button_size = Vector2::new(row_size.x * 2 / 3, row_size.y);
+    for (index, (action, text)) in [
+        (SettingsMenuAction::Apply, "Apply"),
+        (SettingsMenuAction::Revert, "Revert"),
+        (SettingsMenuAction::Defaults, "Defaults"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        result.push(
+            make_button_base(
+                world,
+                Vector2::new(location.x + button_size.x * index as i32, y + row_size.y),
+                button_size,
+                None,
+                color,
+                layer,
+            )
+            .with(Text {
+                text: text.to_owned(),
+                color: sdl2::pixels::Color::WHITE,
+                visible: true,
+                offset: Vector2::new(10, 0),
+            })
+            .with(action)
+            .with(Focusable::default())
+            .build(),
+        );
+    }
+    result
+}
+
+///System that writes values selected in the settings menu into `PendingSettings`,
+/// handles Apply/Revert/Defaults buttons and keeps displayed values up to date
+pub struct SettingsMenuSystem {
+    reader: ReaderId<UiEvent>,
+}
+
+impl SettingsMenuSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            reader: world
+                .entry::<EventChannel<UiEvent>>()
+                .or_insert_with(EventChannel::new)
+                .register_reader(),
+        }
+    }
+}
+
+impl<'a> System<'a> for SettingsMenuSystem {
+    type SystemData = (
+        ReadStorage<'a, SettingsValue>,
+        ReadStorage<'a, SettingsValueDisplay>,
+        ReadStorage<'a, SettingsMenuAction>,
+        WriteStorage<'a, Text>,
+        Write<'a, PendingSettings>,
+        Write<'a, Settings>,
+        Read<'a, SettingsInfo>,
+        Read<'a, EventChannel<UiEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (values, displays, actions, mut text, mut pending, mut settings, info, events): Self::SystemData,
+    ) {
+        for event in events.read(&mut self.reader) {
+            let UiEvent::Clicked(entity) = event else {
+                continue;
+            };
+            if let Some(value) = values.get(*entity) {
+                pending.0.write(value.name.clone(), value.value);
+            }
+            match actions.get(*entity) {
+                Some(SettingsMenuAction::Apply) => {
+                    for (name, value) in pending.0.values() {
+                        if settings.get(name.clone()) != Some(value) {
+                            settings.write(name.clone(), *value);
+                        }
+                    }
+                }
+                Some(SettingsMenuAction::Revert) => pending.0 = settings.clone(),
+                Some(SettingsMenuAction::Defaults) => {
+                    for (name, value) in Settings::default().values() {
+                        pending.0.write(name.clone(), *value);
+                    }
+                }
+                None => {}
+            }
+        }
+        for (text, display) in (&mut text, &displays).join() {
+            text.text = value_name(&info, &pending.0, display.name.as_str());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::components::*;
 use nalgebra::Vector2;
 use crate::game::Game;
+use crate::gamepad::Gamepads;
 use specs::WorldExt;
 
 ///Function for setting up basics of the window and ECS world
@@ -14,7 +15,8 @@ pub fn setup(
         sdl2::VideoSubsystem,
         sdl2::ttf::Sdl2TtfContext,
         sdl2::render::Canvas<sdl2::video::Window>,
-        Game
+        Game,
+        Gamepads,
     ),
     String,
 > {
@@ -26,6 +28,8 @@ pub fn setup(
     let sdl_context: sdl2::Sdl = sdl2::init()?;
     let video_subsystem: sdl2::VideoSubsystem = sdl_context.video()?;
     let ttf_context: sdl2::ttf::Sdl2TtfContext = sdl2::ttf::init().map_err(|e| e.to_string())?;
+    //opens controllers that are already connected, others are opened when they are plugged in
+    let gamepads = Gamepads::new(&sdl_context)?;
     let size = win_size.unwrap_or(Vector2::new(800, 600));
     //setup window
     let window = video_subsystem
@@ -42,5 +46,5 @@ pub fn setup(
     //to allow having transparent textures
     canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
 
-    Ok((world, sdl_context, video_subsystem, ttf_context, canvas,game))
-}
\ No newline at end of file
+    Ok((world, sdl_context, video_subsystem, ttf_context, canvas, game, gamepads))
+}
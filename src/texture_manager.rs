@@ -1,7 +1,10 @@
+use crate::asset_loader::DecodedImage;
+use crate::pack::{AssetPack, DESCRIPTOR_NAME};
+use crate::procedural::{self, GradientDirection};
 use nalgebra::{Vector2,Vector4};
 use sdl2::image::LoadTexture;
-use sdl2::pixels::PixelFormatEnum;
-use std::collections::HashMap;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use std::collections::{HashMap, HashSet};
 
 use std::fs::File;
 use std::io::prelude::*;
@@ -9,7 +12,12 @@ use std::io::prelude::*;
 ///Texture manager holds all of the textures currently loaded.
 /// It is a layer on top of sdl2 TextureCreator that simplifies loading
 pub struct TextureManager<'a> {
+    ///Texture that is drawn instead of textures that were not loaded
     pub error_texture: sdl2::render::Texture<'a>,
+    ///What to do when sprite requests texture that was not loaded
+    pub missing_texture_mode: MissingTextureMode,
+    ///names of the textures that were requested but not found
+    missing_textures: HashSet<String>,
     ///raw texture assets that were loaded
     raw_textures: HashMap<String, sdl2::render::Texture<'a>>,
     textures: HashMap<String, Texture>,
@@ -26,6 +34,18 @@ pub struct Texture {
     pub source_rect: Vector4<u32>,
 }
 
+///Defines how texture manager reacts to requests for textures that were never loaded
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MissingTextureMode {
+    ///Error texture is used without any messages
+    #[default]
+    Silent,
+    ///Error texture is used and message is printed the first time each name is requested
+    LogOnce,
+    ///Rendering fails with an error naming the missing texture
+    Error,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Assets {
     pub textures: Vec<Asset>,
@@ -42,42 +62,19 @@ impl<'a> TextureManager<'a> {
         creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
     ) -> Result<Self, String> {
         let mut texture = creator
-            .create_texture_streaming(PixelFormatEnum::RGB24, 64, 64)
+            .create_texture_streaming(PixelFormatEnum::RGBA32, 64, 64)
             .map_err(|e| e.to_string())?;
         //create a checkerboard black-purple pattern that will be used as fallback texture
         texture.with_lock(None, |buffer: &mut [u8], pitch: usize| {
-            for y in 0..32 {
-                for x in 0..32 {
-                    let offset = y * pitch + x * 3;
-                    buffer[offset] = 255;
-                    buffer[offset + 1] = 0;
-                    buffer[offset + 2] = 255;
-                }
-                for x in 32..64 {
-                    let offset = y * pitch + x * 3;
-                    buffer[offset] = 0;
-                    buffer[offset + 1] = 0;
-                    buffer[offset + 2] = 0;
-                }
-            }
-            for y in 32..64 {
-                for x in 0..32 {
-                    let offset = y * pitch + x * 3;
-                    buffer[offset] = 0;
-                    buffer[offset + 1] = 0;
-                    buffer[offset + 2] = 0;
-                }
-                for x in 32..64 {
-                    let offset = y * pitch + x * 3;
-                    buffer[offset] = 255;
-                    buffer[offset + 1] = 0;
-                    buffer[offset + 2] = 255;
-                }
-            }
+            procedural::fill_pixels(buffer, pitch, 64, 64, |x, y| {
+                procedural::checkerboard(x, y, 32, Color::RGB(255, 0, 255), Color::BLACK)
+            });
         })?;
 
         Ok(Self {
             error_texture: texture,
+            missing_texture_mode: MissingTextureMode::default(),
+            missing_textures: HashSet::new(),
             textures: HashMap::new(),
             raw_textures: HashMap::new(),
             text_textures: HashMap::new(),
@@ -93,6 +90,98 @@ impl<'a> TextureManager<'a> {
         return self.textures.get(name);
     }
 
+    ///Replaces texture that is drawn in place of missing textures
+    pub fn set_error_texture(&mut self, texture: sdl2::render::Texture<'a>) {
+        self.error_texture = texture;
+    }
+
+    ///Loads texture from file and uses it in place of missing textures
+    pub fn load_error_texture(&mut self, path: &str) -> Result<(), String> {
+        self.error_texture = self.creator.load_texture(std::path::Path::new(path))?;
+        Ok(())
+    }
+
+    ///Checks if texture with given name can be drawn either as part of the atlas or as raw texture
+    pub fn has_texture(&self, name: &str) -> bool {
+        match self.textures.get(name) {
+            Some(desc) => self.raw_textures.contains_key(&desc.source_name),
+            None => self.raw_textures.contains_key(name),
+        }
+    }
+
+    ///Finds texture that should be drawn for the given name together with the part of it that should be used.
+    /// If texture is not loaded error texture is returned and the name is recorded according to `missing_texture_mode`
+    pub fn resolve(
+        &mut self,
+        name: &str,
+    ) -> Result<(&sdl2::render::Texture<'a>, Option<sdl2::rect::Rect>), String> {
+        self.resolve_mut(name).map(|(texture, source)| (&*texture, source))
+    }
+
+    ///Same as `resolve` but allows changing texture properties such as color and alpha modulation
+    pub fn resolve_mut(
+        &mut self,
+        name: &str,
+    ) -> Result<(&mut sdl2::render::Texture<'a>, Option<sdl2::rect::Rect>), String> {
+        if !self.has_texture(name) {
+            self.report_missing(name)?;
+            return Ok((&mut self.error_texture, None));
+        }
+        let (source_name, source) = match self.textures.get(name) {
+            Some(desc) => (
+                desc.source_name.as_str(),
+                Some(sdl2::rect::Rect::new(
+                    desc.source_rect.x as i32,
+                    desc.source_rect.y as i32,
+                    desc.source_rect.z,
+                    desc.source_rect.w,
+                )),
+            ),
+            None => (name, None),
+        };
+        let texture = self
+            .raw_textures
+            .get_mut(source_name)
+            .ok_or_else(|| format!("Texture {} was requested but never loaded", name))?;
+        Ok((texture, source))
+    }
+
+    fn report_missing(&mut self, name: &str) -> Result<(), String> {
+        let first_time = !self.missing_textures.contains(name);
+        if first_time {
+            self.missing_textures.insert(name.to_owned());
+        }
+        match self.missing_texture_mode {
+            MissingTextureMode::Silent => Ok(()),
+            MissingTextureMode::LogOnce => {
+                if first_time {
+                    eprintln!("Texture {} was requested but never loaded", name);
+                }
+                Ok(())
+            }
+            MissingTextureMode::Error => {
+                Err(format!("Texture {} was requested but never loaded", name))
+            }
+        }
+    }
+
+    ///Returns names of all textures that were requested for drawing but are still not loaded
+    pub fn missing_textures(&self) -> Vec<String> {
+        let mut result: Vec<String> = self
+            .missing_textures
+            .iter()
+            .filter(|name| !self.has_texture(name))
+            .cloned()
+            .collect();
+        result.sort();
+        result
+    }
+
+    ///Forgets all of the reported missing textures, allowing them to be logged again
+    pub fn clear_missing_textures(&mut self) {
+        self.missing_textures.clear();
+    }
+
     pub fn load_from_descriptor(&mut self) -> Result<(), String> {
         use std::fs::File;
         use std::io::prelude::*;
@@ -113,6 +202,162 @@ impl<'a> TextureManager<'a> {
         Ok(())
     }
 
+    ///Loads all textures described by the asset descriptor stored in the asset pack
+    pub fn load_from_pack(&mut self, pack: &AssetPack) -> Result<(), String> {
+        let data = pack.read_string(DESCRIPTOR_NAME)?;
+        let assets = serde_json::from_str::<Assets>(data.as_str()).map_err(|e| e.to_string())?;
+        for asset in assets.textures {
+            let bytes = pack.read(asset.path.as_str())?;
+            self.raw_textures
+                .insert(asset.name.clone(), self.creator.load_texture_bytes(&bytes)?);
+        }
+        Ok(())
+    }
+
+    ///Creates texture from the image decoded by the `AssetLoader`
+    pub fn load_decoded(&mut self, name: String, image: &DecodedImage) -> Result<(), String> {
+        let mut texture = self
+            .creator
+            .create_texture_static(PixelFormatEnum::RGBA32, image.width, image.height)
+            .map_err(|e| e.to_string())?;
+        texture
+            .update(None, &image.pixels, image.pitch)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        self.raw_textures.insert(name, texture);
+        Ok(())
+    }
+
+    ///Creates texture with given name from RGBA32 pixels, replacing any texture with the same name.
+    /// Texture is created as streaming so it can be cheaply updated later
+    pub fn create_from_pixels(
+        &mut self,
+        name: String,
+        size: Vector2<u32>,
+        pixels: &[u8],
+    ) -> Result<(), String> {
+        if pixels.len() < procedural::pixel_buffer_len(size.x, size.y)? {
+            return Err(format!("Not enough pixel data for texture {}", name));
+        }
+        let mut texture = self
+            .creator
+            .create_texture_streaming(PixelFormatEnum::RGBA32, size.x, size.y)
+            .map_err(|e| e.to_string())?;
+        texture
+            .update(None, pixels, size.x as usize * 4)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        self.textures.remove(&name);
+        self.raw_textures.insert(name, texture);
+        Ok(())
+    }
+
+    ///Creates texture with given name by calling generator for every pixel
+    pub fn create_with<F: FnMut(u32, u32) -> Color>(
+        &mut self,
+        name: String,
+        size: Vector2<u32>,
+        generator: F,
+    ) -> Result<(), String> {
+        let pixels = procedural::generate_pixels(size.x, size.y, generator)?;
+        self.create_from_pixels(name, size, &pixels)
+    }
+
+    ///Creates texture filled with single color
+    pub fn create_solid(
+        &mut self,
+        name: String,
+        size: Vector2<u32>,
+        color: Color,
+    ) -> Result<(), String> {
+        self.create_with(name, size, |_, _| color)
+    }
+
+    ///Creates texture that smoothly changes from one color to another
+    pub fn create_gradient(
+        &mut self,
+        name: String,
+        size: Vector2<u32>,
+        from: Color,
+        to: Color,
+        direction: GradientDirection,
+    ) -> Result<(), String> {
+        self.create_with(name, size, |x, y| {
+            procedural::gradient(x, y, size.x, size.y, from, to, direction)
+        })
+    }
+
+    ///Creates texture filled with value noise blended between two colors
+    ///
+    /// cell_size: size of the noise features in pixels
+    pub fn create_noise(
+        &mut self,
+        name: String,
+        size: Vector2<u32>,
+        cell_size: u32,
+        seed: u32,
+        from: Color,
+        to: Color,
+    ) -> Result<(), String> {
+        self.create_with(name, size, |x, y| {
+            procedural::lerp_color(from, to, procedural::value_noise(x, y, cell_size, seed))
+        })
+    }
+
+    ///Replaces pixels of the existing texture. Pixels are in RGBA32 format and must cover whole texture.
+    /// Useful for textures that change every frame such as minimaps
+    pub fn update_pixels(&mut self, name: &str, pixels: &[u8]) -> Result<(), String> {
+        let texture = self
+            .raw_textures
+            .get_mut(name)
+            .ok_or_else(|| format!("Texture {} does not exist", name))?;
+        let query = texture.query();
+        if pixels.len() < procedural::pixel_buffer_len(query.width, query.height)? {
+            return Err(format!("Not enough pixel data for texture {}", name));
+        }
+        texture
+            .update(None, pixels, query.width as usize * 4)
+            .map_err(|e| e.to_string())
+    }
+
+    ///Regenerates pixels of the existing texture by calling generator for every pixel
+    pub fn update_with<F: FnMut(u32, u32) -> Color>(
+        &mut self,
+        name: &str,
+        generator: F,
+    ) -> Result<(), String> {
+        let query = self
+            .raw_textures
+            .get(name)
+            .ok_or_else(|| format!("Texture {} does not exist", name))?
+            .query();
+        let pixels = procedural::generate_pixels(query.width, query.height, generator)?;
+        self.update_pixels(name, &pixels)
+    }
+
+    ///Creates texture that can be used as render target
+    pub(crate) fn create_target_texture(
+        &self,
+        size: Vector2<u32>,
+    ) -> Result<sdl2::render::Texture<'a>, String> {
+        let mut texture = self
+            .creator
+            .create_texture_target(PixelFormatEnum::RGBA32, size.x, size.y)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        Ok(texture)
+    }
+
+    ///Removes raw texture from the manager, used to render into texture while other textures are in use
+    pub(crate) fn take_raw(&mut self, name: &str) -> Option<sdl2::render::Texture<'a>> {
+        self.raw_textures.remove(name)
+    }
+
+    pub(crate) fn insert_raw(&mut self, name: String, texture: sdl2::render::Texture<'a>) {
+        self.textures.remove(&name);
+        self.raw_textures.insert(name, texture);
+    }
+
     pub fn get_text(&'a self, name: &'a str) -> Option<&(Vector2<u32>, sdl2::render::Texture<'a>)> {
         return self.text_textures.get(name);
     }
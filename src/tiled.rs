@@ -0,0 +1,928 @@
+use crate::components::{Position, Rectangle, Renderable};
+use crate::texture_manager::TextureManager;
+use crate::tilemap::{Tile, Tilemap};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use nalgebra::{Vector2, Vector4};
+use specs::{Builder, Entity, EntityBuilder, World, WorldExt};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const ROTATED_HEXAGONAL: u32 = 0x1000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL);
+
+///Splits raw gid stored in the file into global tile id and horizontal, vertical and diagonal flips
+fn split_gid(raw: u32) -> (u32, bool, bool, bool) {
+    (
+        raw & GID_MASK,
+        raw & FLIPPED_HORIZONTALLY != 0,
+        raw & FLIPPED_VERTICALLY != 0,
+        raw & FLIPPED_DIAGONALLY != 0,
+    )
+}
+
+///Value of the custom property set in Tiled
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    ///Strings, colors, files and any other property types
+    String(String),
+}
+
+///Object placed in the object layer
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    ///Class of the object, called type in older versions of Tiled
+    pub class: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    ///Global tile id if this is a tile object, without flip flags
+    pub gid: Option<u32>,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub properties: HashMap<String, TiledProperty>,
+}
+
+///Tileset used by the map. Only tilesets made from single image are supported
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    pub name: String,
+    ///Path to the tileset image relative to the working directory
+    pub image: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tile_count: u32,
+}
+
+///Layer of the map. Layers from groups are flattened in the order they are drawn
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledLayer {
+    Tiles {
+        name: String,
+        visible: bool,
+        offset: Vector2<i32>,
+        ///Global tile ids including flip flags, stored row by row
+        data: Vec<u32>,
+    },
+    Objects {
+        name: String,
+        visible: bool,
+        offset: Vector2<i32>,
+        objects: Vec<TiledObject>,
+    },
+}
+
+///Map loaded from Tiled json(.tmj) or xml(.tmx) file
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+}
+
+///Function that adds components to the entity created from the object
+pub type ObjectSpawner = Box<dyn for<'w> Fn(EntityBuilder<'w>, &TiledObject) -> EntityBuilder<'w>>;
+
+///Function that adds components to the entity created from the object that has given property
+pub type PropertySpawner =
+    Box<dyn for<'w> Fn(EntityBuilder<'w>, &TiledObject, &TiledProperty) -> EntityBuilder<'w>>;
+
+///Registry of user functions that turn Tiled objects into components.
+/// Every object gets `Position` and `Rectangle`, everything else comes from the registry
+#[derive(Default)]
+pub struct TiledObjectRegistry {
+    classes: HashMap<String, ObjectSpawner>,
+    properties: HashMap<String, PropertySpawner>,
+}
+
+impl TiledObjectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Registers function that is called for every object with given class
+    pub fn register_class<F>(&mut self, class: &str, spawner: F)
+    where
+        F: for<'w> Fn(EntityBuilder<'w>, &TiledObject) -> EntityBuilder<'w> + 'static,
+    {
+        self.classes.insert(class.to_owned(), Box::new(spawner));
+    }
+
+    ///Registers function that is called for every object that has custom property with given name
+    pub fn register_property<F>(&mut self, name: &str, spawner: F)
+    where
+        F: for<'w> Fn(EntityBuilder<'w>, &TiledObject, &TiledProperty) -> EntityBuilder<'w>
+            + 'static,
+    {
+        self.properties.insert(name.to_owned(), Box::new(spawner));
+    }
+
+    fn build<'w>(&self, mut builder: EntityBuilder<'w>, object: &TiledObject) -> EntityBuilder<'w> {
+        if let Some(spawner) = self.classes.get(&object.class) {
+            builder = spawner(builder, object);
+        }
+        //sort names so that components are added in predictable order
+        let mut names: Vec<&String> = object.properties.keys().collect();
+        names.sort();
+        for name in names {
+            if let Some(spawner) = self.properties.get(name) {
+                builder = spawner(builder, object, &object.properties[name]);
+            }
+        }
+        builder
+    }
+}
+
+fn read_text(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut buf: Vec<u8> = Vec::new();
+    file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+    String::from_utf8(buf).map_err(|e| e.to_string())
+}
+
+fn is_xml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("tmx") | Some("tsx") | Some("xml")
+    )
+}
+
+///Resolves path written in the file relative to the directory of that file
+fn relative_to(file: &Path, path: &str) -> PathBuf {
+    file.parent().unwrap_or_else(|| Path::new("")).join(path)
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let value = |c: u8| -> Result<u32, String> {
+        match c {
+            b'A'..=b'Z' => Ok((c - b'A') as u32),
+            b'a'..=b'z' => Ok((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Ok((c - b'0') as u32 + 52),
+            b'+' => Ok(62),
+            b'/' => Ok(63),
+            _ => Err("Invalid base64 tile data".to_owned()),
+        }
+    };
+    let chars: Vec<u8> = text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+        .collect();
+    let mut result = Vec::with_capacity(chars.len() * 3 / 4);
+    for chunk in chars.chunks(4) {
+        let mut buffer = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            buffer |= value(*c)? << (18 - i * 6);
+        }
+        let bytes = buffer.to_be_bytes();
+        result.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Ok(result)
+}
+
+///Decodes tile data that was stored as base64 string with optional compression
+fn decode_tile_data(text: &str, compression: &str) -> Result<Vec<u32>, String> {
+    let raw = decode_base64(text)?;
+    let mut bytes: Vec<u8> = Vec::new();
+    match compression {
+        "" => bytes = raw,
+        "zlib" => {
+            ZlibDecoder::new(raw.as_slice())
+                .read_to_end(&mut bytes)
+                .map_err(|e| e.to_string())?;
+        }
+        "gzip" => {
+            GzDecoder::new(raw.as_slice())
+                .read_to_end(&mut bytes)
+                .map_err(|e| e.to_string())?;
+        }
+        other => return Err(format!("Unsupported tile data compression {}", other)),
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+fn parse_csv(text: &str) -> Result<Vec<u32>, String> {
+    text.split(',')
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<u32>().map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn check_tileset_layout(name: &str, spacing: u32, margin: u32) -> Result<(), String> {
+    if spacing != 0 || margin != 0 {
+        return Err(format!(
+            "Tileset {} uses spacing or margin which is not supported",
+            name
+        ));
+    }
+    Ok(())
+}
+
+///Parsing of the json based formats(.tmj and .tsj)
+mod json {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Property {
+        name: String,
+        #[serde(rename = "type", default)]
+        kind: String,
+        value: serde_json::Value,
+    }
+
+    #[derive(Deserialize)]
+    struct Object {
+        #[serde(default)]
+        id: u32,
+        #[serde(default)]
+        name: String,
+        #[serde(rename = "type", default)]
+        kind: String,
+        #[serde(default)]
+        class: String,
+        #[serde(default)]
+        x: f32,
+        #[serde(default)]
+        y: f32,
+        #[serde(default)]
+        width: f32,
+        #[serde(default)]
+        height: f32,
+        gid: Option<u32>,
+        #[serde(default)]
+        properties: Vec<Property>,
+    }
+
+    fn default_visible() -> bool {
+        true
+    }
+
+    #[derive(Deserialize)]
+    struct Layer {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default = "default_visible")]
+        visible: bool,
+        #[serde(default)]
+        offsetx: f32,
+        #[serde(default)]
+        offsety: f32,
+        data: Option<serde_json::Value>,
+        #[serde(default)]
+        encoding: String,
+        #[serde(default)]
+        compression: String,
+        #[serde(default)]
+        objects: Vec<Object>,
+        #[serde(default)]
+        layers: Vec<Layer>,
+    }
+
+    #[derive(Deserialize)]
+    struct Tileset {
+        #[serde(default)]
+        firstgid: u32,
+        source: Option<String>,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        image: String,
+        #[serde(default)]
+        imagewidth: u32,
+        #[serde(default)]
+        imageheight: u32,
+        #[serde(default)]
+        tilewidth: u32,
+        #[serde(default)]
+        tileheight: u32,
+        #[serde(default)]
+        tilecount: u32,
+        #[serde(default)]
+        spacing: u32,
+        #[serde(default)]
+        margin: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct Map {
+        width: u32,
+        height: u32,
+        tilewidth: u32,
+        tileheight: u32,
+        #[serde(default)]
+        infinite: bool,
+        #[serde(default)]
+        tilesets: Vec<Tileset>,
+        #[serde(default)]
+        layers: Vec<Layer>,
+    }
+
+    fn convert_property(property: &Property) -> TiledProperty {
+        match (property.kind.as_str(), &property.value) {
+            ("bool", serde_json::Value::Bool(v)) => TiledProperty::Bool(*v),
+            ("int", v) if v.is_i64() => TiledProperty::Int(v.as_i64().unwrap_or_default()),
+            ("float", v) if v.is_number() => TiledProperty::Float(v.as_f64().unwrap_or_default()),
+            (_, serde_json::Value::String(v)) => TiledProperty::String(v.clone()),
+            (_, v) => TiledProperty::String(v.to_string()),
+        }
+    }
+
+    fn convert_object(object: &Object) -> TiledObject {
+        let (gid, flip_horizontal, flip_vertical, _) = split_gid(object.gid.unwrap_or_default());
+        TiledObject {
+            id: object.id,
+            name: object.name.clone(),
+            class: if object.class.is_empty() {
+                object.kind.clone()
+            } else {
+                object.class.clone()
+            },
+            x: object.x,
+            y: object.y,
+            width: object.width,
+            height: object.height,
+            gid: Some(gid).filter(|gid| *gid != 0),
+            flip_horizontal,
+            flip_vertical,
+            properties: object
+                .properties
+                .iter()
+                .map(|p| (p.name.clone(), convert_property(p)))
+                .collect(),
+        }
+    }
+
+    fn convert_layers(
+        layers: &[Layer],
+        parent_offset: Vector2<i32>,
+        parent_visible: bool,
+        result: &mut Vec<TiledLayer>,
+    ) -> Result<(), String> {
+        for layer in layers {
+            let offset = parent_offset + Vector2::new(layer.offsetx as i32, layer.offsety as i32);
+            let visible = parent_visible && layer.visible;
+            match layer.kind.as_str() {
+                "tilelayer" => {
+                    let data = match &layer.data {
+                        Some(serde_json::Value::String(text)) if layer.encoding == "base64" => {
+                            decode_tile_data(text, layer.compression.as_str())?
+                        }
+                        Some(serde_json::Value::Array(values)) => values
+                            .iter()
+                            .map(|v| v.as_u64().unwrap_or_default() as u32)
+                            .collect(),
+                        _ => return Err(format!("Layer {} has unsupported tile data", layer.name)),
+                    };
+                    result.push(TiledLayer::Tiles {
+                        name: layer.name.clone(),
+                        visible,
+                        offset,
+                        data,
+                    });
+                }
+                "objectgroup" => result.push(TiledLayer::Objects {
+                    name: layer.name.clone(),
+                    visible,
+                    offset,
+                    objects: layer.objects.iter().map(convert_object).collect(),
+                }),
+                "group" => convert_layers(&layer.layers, offset, visible, result)?,
+                //image layers are not supported
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn parse_tileset(
+        path: &Path,
+        text: &str,
+        first_gid: u32,
+    ) -> Result<TiledTileset, String> {
+        let tileset: Tileset = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        convert_tileset(path, &tileset, first_gid)
+    }
+
+    fn convert_tileset(
+        path: &Path,
+        tileset: &Tileset,
+        first_gid: u32,
+    ) -> Result<TiledTileset, String> {
+        check_tileset_layout(tileset.name.as_str(), tileset.spacing, tileset.margin)?;
+        Ok(TiledTileset {
+            first_gid,
+            name: tileset.name.clone(),
+            image: relative_to(path, tileset.image.as_str())
+                .to_string_lossy()
+                .into_owned(),
+            image_width: tileset.imagewidth,
+            image_height: tileset.imageheight,
+            tile_width: tileset.tilewidth,
+            tile_height: tileset.tileheight,
+            tile_count: tileset.tilecount,
+        })
+    }
+
+    pub(super) fn parse_map(path: &Path, text: &str) -> Result<TiledMap, String> {
+        let map: Map = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if map.infinite {
+            return Err("Infinite maps are not supported".to_owned());
+        }
+        let mut tilesets = Vec::new();
+        for tileset in &map.tilesets {
+            tilesets.push(match &tileset.source {
+                Some(source) => load_tileset(&relative_to(path, source), tileset.firstgid)?,
+                None => convert_tileset(path, tileset, tileset.firstgid)?,
+            });
+        }
+        let mut layers = Vec::new();
+        convert_layers(&map.layers, Vector2::new(0, 0), true, &mut layers)?;
+        Ok(TiledMap {
+            width: map.width,
+            height: map.height,
+            tile_width: map.tilewidth,
+            tile_height: map.tileheight,
+            tilesets,
+            layers,
+        })
+    }
+}
+
+///Parsing of the xml based formats(.tmx and .tsx)
+mod xml {
+    use super::*;
+    use roxmltree::{Document, Node};
+
+    fn attribute<T: std::str::FromStr + Default>(node: &Node, name: &str) -> T {
+        node.attribute(name)
+            .and_then(|v| v.parse::<T>().ok())
+            .unwrap_or_default()
+    }
+
+    fn text_attribute(node: &Node, name: &str) -> String {
+        node.attribute(name).unwrap_or_default().to_owned()
+    }
+
+    fn is_visible(node: &Node) -> bool {
+        node.attribute("visible") != Some("0")
+    }
+
+    fn convert_properties(node: &Node) -> HashMap<String, TiledProperty> {
+        let mut result = HashMap::new();
+        for properties in node.children().filter(|n| n.has_tag_name("properties")) {
+            for property in properties.children().filter(|n| n.has_tag_name("property")) {
+                //multiline strings are stored as text instead of value attribute
+                let value = property
+                    .attribute("value")
+                    .or_else(|| property.text())
+                    .unwrap_or_default();
+                let converted = match property.attribute("type").unwrap_or("string") {
+                    "bool" => TiledProperty::Bool(value == "true"),
+                    "int" => value
+                        .parse::<i64>()
+                        .map(TiledProperty::Int)
+                        .unwrap_or_else(|_| TiledProperty::String(value.to_owned())),
+                    "float" => value
+                        .parse::<f64>()
+                        .map(TiledProperty::Float)
+                        .unwrap_or_else(|_| TiledProperty::String(value.to_owned())),
+                    _ => TiledProperty::String(value.to_owned()),
+                };
+                result.insert(text_attribute(&property, "name"), converted);
+            }
+        }
+        result
+    }
+
+    fn convert_object(node: &Node) -> TiledObject {
+        let class = node
+            .attribute("class")
+            .or_else(|| node.attribute("type"))
+            .unwrap_or_default()
+            .to_owned();
+        let raw = node.attribute("gid").and_then(|v| v.parse::<u32>().ok());
+        let (gid, flip_horizontal, flip_vertical, _) = split_gid(raw.unwrap_or_default());
+        TiledObject {
+            id: attribute(node, "id"),
+            name: text_attribute(node, "name"),
+            class,
+            x: attribute(node, "x"),
+            y: attribute(node, "y"),
+            width: attribute(node, "width"),
+            height: attribute(node, "height"),
+            gid: Some(gid).filter(|gid| *gid != 0),
+            flip_horizontal,
+            flip_vertical,
+            properties: convert_properties(node),
+        }
+    }
+
+    fn convert_tile_data(layer: &Node) -> Result<Vec<u32>, String> {
+        let data = layer
+            .children()
+            .find(|n| n.has_tag_name("data"))
+            .ok_or_else(|| "Tile layer has no data".to_owned())?;
+        if data.children().any(|n| n.has_tag_name("chunk")) {
+            return Err("Infinite maps are not supported".to_owned());
+        }
+        let text = data.text().unwrap_or_default();
+        match data.attribute("encoding") {
+            Some("csv") => parse_csv(text),
+            Some("base64") => decode_tile_data(text, data.attribute("compression").unwrap_or("")),
+            //deprecated format where every tile is separate element
+            _ => Ok(data
+                .children()
+                .filter(|n| n.has_tag_name("tile"))
+                .map(|n| attribute::<u32>(&n, "gid"))
+                .collect()),
+        }
+    }
+
+    fn convert_layers(
+        parent: &Node,
+        parent_offset: Vector2<i32>,
+        parent_visible: bool,
+        result: &mut Vec<TiledLayer>,
+    ) -> Result<(), String> {
+        for node in parent.children().filter(|n| n.is_element()) {
+            let offset = parent_offset
+                + Vector2::new(
+                    attribute::<f32>(&node, "offsetx") as i32,
+                    attribute::<f32>(&node, "offsety") as i32,
+                );
+            let visible = parent_visible && is_visible(&node);
+            match node.tag_name().name() {
+                "layer" => result.push(TiledLayer::Tiles {
+                    name: text_attribute(&node, "name"),
+                    visible,
+                    offset,
+                    data: convert_tile_data(&node)?,
+                }),
+                "objectgroup" => result.push(TiledLayer::Objects {
+                    name: text_attribute(&node, "name"),
+                    visible,
+                    offset,
+                    objects: node
+                        .children()
+                        .filter(|n| n.has_tag_name("object"))
+                        .map(|n| convert_object(&n))
+                        .collect(),
+                }),
+                "group" => convert_layers(&node, offset, visible, result)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn convert_tileset(path: &Path, node: &Node, first_gid: u32) -> Result<TiledTileset, String> {
+        let name = text_attribute(node, "name");
+        check_tileset_layout(
+            name.as_str(),
+            attribute(node, "spacing"),
+            attribute(node, "margin"),
+        )?;
+        let image = node
+            .children()
+            .find(|n| n.has_tag_name("image"))
+            .ok_or_else(|| format!("Tileset {} is not based on single image", name))?;
+        Ok(TiledTileset {
+            first_gid,
+            image: relative_to(path, image.attribute("source").unwrap_or_default())
+                .to_string_lossy()
+                .into_owned(),
+            image_width: attribute(&image, "width"),
+            image_height: attribute(&image, "height"),
+            tile_width: attribute(node, "tilewidth"),
+            tile_height: attribute(node, "tileheight"),
+            tile_count: attribute(node, "tilecount"),
+            name,
+        })
+    }
+
+    pub(super) fn parse_tileset(
+        path: &Path,
+        text: &str,
+        first_gid: u32,
+    ) -> Result<TiledTileset, String> {
+        let document = Document::parse(text).map_err(|e| e.to_string())?;
+        convert_tileset(path, &document.root_element(), first_gid)
+    }
+
+    pub(super) fn parse_map(path: &Path, text: &str) -> Result<TiledMap, String> {
+        let document = Document::parse(text).map_err(|e| e.to_string())?;
+        let map = document.root_element();
+        if attribute::<u32>(&map, "infinite") != 0 {
+            return Err("Infinite maps are not supported".to_owned());
+        }
+        let mut tilesets = Vec::new();
+        for node in map.children().filter(|n| n.has_tag_name("tileset")) {
+            let first_gid = attribute(&node, "firstgid");
+            tilesets.push(match node.attribute("source") {
+                Some(source) => load_tileset(&relative_to(path, source), first_gid)?,
+                None => convert_tileset(path, &node, first_gid)?,
+            });
+        }
+        let mut layers = Vec::new();
+        convert_layers(&map, Vector2::new(0, 0), true, &mut layers)?;
+        Ok(TiledMap {
+            width: attribute(&map, "width"),
+            height: attribute(&map, "height"),
+            tile_width: attribute(&map, "tilewidth"),
+            tile_height: attribute(&map, "tileheight"),
+            tilesets,
+            layers,
+        })
+    }
+}
+
+///Loads external tileset file(.tsj or .tsx)
+fn load_tileset(path: &Path, first_gid: u32) -> Result<TiledTileset, String> {
+    let text = read_text(path)?;
+    if is_xml(path) {
+        xml::parse_tileset(path, text.as_str(), first_gid)
+    } else {
+        json::parse_tileset(path, text.as_str(), first_gid)
+    }
+}
+
+impl TiledMap {
+    ///Loads map from file, format is picked based on the extension: .tmx for xml and anything else for json
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let path = Path::new(path);
+        let text = read_text(path)?;
+        if is_xml(path) {
+            xml::parse_map(path, text.as_str())
+        } else {
+            json::parse_map(path, text.as_str())
+        }
+    }
+
+    ///Name under which tileset texture is registered in the texture manager.
+    /// First gid is included because tileset names don't have to be unique
+    pub fn tileset_texture_name(tileset: &TiledTileset) -> String {
+        format!("tileset_{}_{}", tileset.first_gid, tileset.name)
+    }
+
+    ///Loads all tileset images into the texture manager
+    pub fn load_tilesets(&self, textures: &mut TextureManager) -> Result<(), String> {
+        for tileset in &self.tilesets {
+            textures.load(
+                Vector4::new(0, 0, tileset.image_width, tileset.image_height),
+                Self::tileset_texture_name(tileset),
+                tileset.image.clone(),
+            )?;
+        }
+        Ok(())
+    }
+
+    ///Finds tileset that the global tile id belongs to
+    fn tileset_for(&self, gid: u32) -> Option<usize> {
+        self.tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, tileset)| gid >= tileset.first_gid)
+            .max_by_key(|(_, tileset)| tileset.first_gid)
+            .map(|(index, _)| index)
+    }
+
+    ///Creates entities for all layers of the map.
+    /// Each tile layer becomes one `Tilemap` entity per tileset used in it, with `order` set to the index of the layer.
+    /// Tilesets used by tile layers must have the same tile size as the map,
+    /// and each object becomes entity with `Position`, `Rectangle` and components from the registry.
+    ///
+    /// origin: position of the top left corner of the map in the world
+    ///
+    /// render_layer: render layer that tilemaps will be drawn in
+    ///
+    /// If spawning fails all entities created so far are deleted
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        textures: &mut TextureManager,
+        registry: &TiledObjectRegistry,
+        origin: Vector2<i32>,
+        render_layer: u32,
+    ) -> Result<Vec<Entity>, String> {
+        self.load_tilesets(textures)?;
+        let mut entities = Vec::new();
+        match self.spawn_layers(world, registry, origin, render_layer, &mut entities) {
+            Ok(()) => Ok(entities),
+            Err(e) => {
+                //entities were just created so they can't be dead
+                let _ = world.delete_entities(&entities);
+                Err(e)
+            }
+        }
+    }
+
+    fn spawn_layers(
+        &self,
+        world: &mut World,
+        registry: &TiledObjectRegistry,
+        origin: Vector2<i32>,
+        render_layer: u32,
+        entities: &mut Vec<Entity>,
+    ) -> Result<(), String> {
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            match layer {
+                TiledLayer::Tiles {
+                    name,
+                    visible,
+                    offset,
+                    data,
+                } => {
+                    let mut maps: Vec<Option<Tilemap>> = vec![None; self.tilesets.len()];
+                    for (index, raw) in data.iter().enumerate() {
+                        let (gid, flip_horizontal, flip_vertical, flip_diagonal) = split_gid(*raw);
+                        if gid == 0 {
+                            continue;
+                        }
+                        let Some(tileset_index) = self.tileset_for(gid) else {
+                            continue;
+                        };
+                        let tileset = &self.tilesets[tileset_index];
+                        //tiles are drawn in cells of the tile size, so it has to match the map grid
+                        if tileset.tile_width != self.tile_width
+                            || tileset.tile_height != self.tile_height
+                        {
+                            return Err(format!(
+                                "Tileset {} has tiles of size {}x{} which differs from the map tile size {}x{}",
+                                tileset.name,
+                                tileset.tile_width,
+                                tileset.tile_height,
+                                self.tile_width,
+                                self.tile_height
+                            ));
+                        }
+                        let map = maps[tileset_index].get_or_insert_with(|| {
+                            let mut map = Tilemap::new(
+                                Self::tileset_texture_name(tileset),
+                                Vector2::new(tileset.tile_width, tileset.tile_height),
+                                self.width,
+                                self.height,
+                            );
+                            map.layers[0].name = name.clone();
+                            map.order = layer_index as i32;
+                            map
+                        });
+                        let index = index as u32;
+                        map.set_tile(
+                            0,
+                            index % self.width.max(1),
+                            index / self.width.max(1),
+                            Some(Tile {
+                                id: gid - tileset.first_gid,
+                                flip_horizontal,
+                                flip_vertical,
+                                flip_diagonal,
+                            }),
+                        )?;
+                    }
+                    for map in maps.into_iter().flatten() {
+                        entities.push(
+                            world
+                                .create_entity()
+                                .with(Position {
+                                    x: origin.x + offset.x,
+                                    y: origin.y + offset.y,
+                                })
+                                .with(map)
+                                .with(Renderable::new(*visible, render_layer))
+                                .build(),
+                        );
+                    }
+                }
+                TiledLayer::Objects {
+                    offset, objects, ..
+                } => {
+                    for object in objects {
+                        //tile objects are positioned using bottom left corner
+                        let y = if object.gid.is_some() {
+                            object.y - object.height
+                        } else {
+                            object.y
+                        };
+                        let builder = world
+                            .create_entity()
+                            .with(Position {
+                                x: origin.x + offset.x + object.x.round() as i32,
+                                y: origin.y + offset.y + y.round() as i32,
+                            })
+                            .with(Rectangle {
+                                width: object.width.round() as i32,
+                                height: object.height.round() as i32,
+                            });
+                        entities.push(registry.build(builder, object).build());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    fn encode_base64(data: &[u8]) -> String {
+        const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut result = String::new();
+        for chunk in data.chunks(3) {
+            let mut buffer = [0u8; 4];
+            buffer[1..=chunk.len()].copy_from_slice(chunk);
+            let value = u32::from_be_bytes(buffer);
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    result.push(CHARS[(value >> (18 - i * 6) & 63) as usize] as char);
+                } else {
+                    result.push('=');
+                }
+            }
+        }
+        result
+    }
+
+    fn tile_bytes(gids: &[u32]) -> Vec<u8> {
+        gids.iter().flat_map(|gid| gid.to_le_bytes()).collect()
+    }
+
+    const GIDS: [u32; 4] = [0, 1, 42, FLIPPED_HORIZONTALLY | FLIPPED_DIAGONALLY | 7];
+
+    #[test]
+    fn base64() {
+        assert_eq!(decode_base64("SGVsbG8=").unwrap(), b"Hello");
+        assert_eq!(decode_base64("SGVs\n bG8h").unwrap(), b"Hello!");
+        assert_eq!(decode_base64("").unwrap(), b"");
+        assert!(decode_base64("SGV*bG8=").is_err());
+        for len in 0..8 {
+            let data: Vec<u8> = (0..len).map(|i| 255 - i * 37).collect();
+            assert_eq!(decode_base64(&encode_base64(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn uncompressed_tile_data() {
+        let text = encode_base64(&tile_bytes(&GIDS));
+        assert_eq!(decode_tile_data(&text, "").unwrap(), GIDS);
+    }
+
+    #[test]
+    fn zlib_tile_data() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tile_bytes(&GIDS)).unwrap();
+        let text = encode_base64(&encoder.finish().unwrap());
+        assert_eq!(decode_tile_data(&text, "zlib").unwrap(), GIDS);
+        assert!(decode_tile_data(&text, "gzip").is_err());
+    }
+
+    #[test]
+    fn gzip_tile_data() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tile_bytes(&GIDS)).unwrap();
+        let text = encode_base64(&encoder.finish().unwrap());
+        assert_eq!(decode_tile_data(&text, "gzip").unwrap(), GIDS);
+    }
+
+    #[test]
+    fn unsupported_compression() {
+        let text = encode_base64(&tile_bytes(&GIDS));
+        assert!(decode_tile_data(&text, "zstd").is_err());
+    }
+
+    #[test]
+    fn gid_flags() {
+        assert_eq!(split_gid(5), (5, false, false, false));
+        assert_eq!(split_gid(GIDS[3]), (7, true, false, true));
+        assert_eq!(
+            split_gid(FLIPPED_VERTICALLY | ROTATED_HEXAGONAL | 3),
+            (3, false, true, false)
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use nalgebra::Vector2;
+use specs::{Component, VecStorage};
+
+///Single tile of the tilemap
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Tile {
+    ///Index of the tile in the tileset, counted from left to right and top to bottom
+    pub id: u32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    ///Swaps x and y axes of the tile, applied before the other flips. Used by Tiled for rotated tiles
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            flip_horizontal: false,
+            flip_vertical: false,
+            flip_diagonal: false,
+        }
+    }
+
+    ///Rotation angle and horizontal and vertical flips that draw the tile with correct orientation.
+    /// Diagonal flip is drawn as 90 degree rotation of the tile with swapped flips
+    pub fn transform(&self) -> (f64, bool, bool) {
+        if self.flip_diagonal {
+            (90.0, self.flip_vertical, !self.flip_horizontal)
+        } else {
+            (0.0, self.flip_horizontal, self.flip_vertical)
+        }
+    }
+}
+
+///One layer of tiles. Layers are drawn in order they are stored in the tilemap
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileLayer {
+    pub name: String,
+    pub visible: bool,
+    ///Tiles stored row by row, None means that nothing is drawn in that cell
+    tiles: Vec<Option<Tile>>,
+}
+
+///Grid of tiles that is drawn using single tileset texture.
+/// Map is drawn starting at the entity's `Position` and only tiles that are visible on the screen are drawn
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct Tilemap {
+    ///Name of the tileset texture in the texture manager
+    pub tileset: String,
+    ///Size of the single tile in the tileset and on the screen
+    pub tile_size: Vector2<u32>,
+    ///Size of the map in tiles, fixed at creation because layers are allocated for it
+    width: u32,
+    height: u32,
+    pub layers: Vec<TileLayer>,
+    pub visible: bool,
+    ///Tilemaps in the same render layer are drawn from the lowest order to the highest,
+    /// order of tilemaps with equal value is not specified
+    pub order: i32,
+}
+
+impl Tilemap {
+    ///Creates empty tilemap with one layer
+    pub fn new(tileset: String, tile_size: Vector2<u32>, width: u32, height: u32) -> Self {
+        let mut result = Self {
+            tileset,
+            tile_size,
+            width,
+            height,
+            layers: Vec::new(),
+            visible: true,
+            order: 0,
+        };
+        result.add_layer("default".to_owned());
+        result
+    }
+
+    ///Width of the map in tiles
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    ///Height of the map in tiles
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    ///Adds new empty layer on top of existing ones and returns its index
+    pub fn add_layer(&mut self, name: String) -> usize {
+        self.layers.push(TileLayer {
+            name,
+            visible: true,
+            tiles: vec![None; (self.width * self.height) as usize],
+        });
+        self.layers.len() - 1
+    }
+
+    ///Returns index of the layer with given name
+    pub fn find_layer(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    ///Returns tile at given cell or None if cell is empty or outside of the map
+    pub fn get_tile(&self, layer: usize, x: u32, y: u32) -> Option<Tile> {
+        let index = self.index(x, y)?;
+        self.layers.get(layer)?.tiles[index]
+    }
+
+    ///Returns tile of the given layer without checking layer index
+    pub fn get_tile_in(&self, layer: &TileLayer, x: u32, y: u32) -> Option<Tile> {
+        layer.tiles[self.index(x, y)?]
+    }
+
+    ///Replaces tile at given cell. Use None to clear the cell
+    pub fn set_tile(
+        &mut self,
+        layer: usize,
+        x: u32,
+        y: u32,
+        tile: Option<Tile>,
+    ) -> Result<(), String> {
+        let index = self
+            .index(x, y)
+            .ok_or_else(|| format!("Tile {}x{} is outside of the map", x, y))?;
+        let layer = self
+            .layers
+            .get_mut(layer)
+            .ok_or_else(|| format!("Tilemap has no layer {}", layer))?;
+        layer.tiles[index] = tile;
+        Ok(())
+    }
+
+    ///Sets every cell of the layer to the given tile
+    pub fn fill(&mut self, layer: usize, tile: Option<Tile>) -> Result<(), String> {
+        let layer = self
+            .layers
+            .get_mut(layer)
+            .ok_or_else(|| format!("Tilemap has no layer {}", layer))?;
+        layer.tiles.fill(tile);
+        Ok(())
+    }
+
+    ///Converts point in the world into the cell of the map that is placed at the map_position
+    pub fn cell_at(&self, map_position: Vector2<i32>, point: Vector2<i32>) -> Option<(u32, u32)> {
+        let local = point - map_position;
+        if local.x < 0 || local.y < 0 {
+            return None;
+        }
+        let (x, y) = (
+            local.x as u32 / self.tile_size.x.max(1),
+            local.y as u32 / self.tile_size.y.max(1),
+        );
+        self.index(x, y).map(|_| (x, y))
+    }
+
+    ///Size of the whole map in pixels
+    pub fn pixel_size(&self) -> Vector2<u32> {
+        Vector2::new(
+            self.width * self.tile_size.x,
+            self.height * self.tile_size.y,
+        )
+    }
+}
@@ -1,15 +1,96 @@
+use crate::actions::{Binding, InputActions};
 use crate::components::*;
+use crate::input::InputState;
 use crate::layers::RenderLayers;
 use nalgebra::Vector2;
+use sdl2::mouse::MouseButton;
+use specs::shrev::EventChannel;
 use specs::{
-    Builder, Component, EntityBuilder, Join, NullStorage, Read, ReadStorage, System, VecStorage,
-    World, WorldExt, WriteStorage,
+    Builder, Component, Entities, Entity, EntityBuilder, Join, NullStorage, Read, ReadStorage,
+    ReaderId, System, VecStorage, World, WorldExt, Write, WriteStorage,
 };
 
+///Position of the mouse in logical coordinates.
+/// Kept for code that doesn't use `InputState`, `MouseDataSystem` fills it from `InputState` every frame
 #[derive(Default)]
 pub struct MouseData {
     pub x: i32,
     pub y: i32,
+    ///True while left mouse button is held down
+    pub left_button: bool,
+}
+
+impl MouseData {
+    ///Creates mouse data from the window coordinates(for example `EventPump::mouse_state`),
+    /// converting them into logical space
+    pub fn from_window(canvas: &crate::render::Canvas, x: i32, y: i32) -> Self {
+        let (x, y) = crate::render::window_to_logical(canvas, x, y);
+        Self {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    ///Creates mouse data from the current mouse state, including button state
+    pub fn from_state(canvas: &crate::render::Canvas, state: &sdl2::mouse::MouseState) -> Self {
+        Self {
+            left_button: state.left(),
+            ..Self::from_window(canvas, state.x(), state.y())
+        }
+    }
+
+    ///Creates mouse data from the input state, which already uses logical coordinates
+    pub fn from_input(input: &InputState) -> Self {
+        Self {
+            x: input.mouse_position.x,
+            y: input.mouse_position.y,
+            left_button: input.is_mouse_down(MouseButton::Left),
+        }
+    }
+}
+
+///System that keeps `MouseData` resource in sync with `InputState`
+pub struct MouseDataSystem;
+
+impl<'a> System<'a> for MouseDataSystem {
+    type SystemData = (Write<'a, MouseData>, Read<'a, InputState>);
+
+    fn run(&mut self, (mut mouse, input): Self::SystemData) {
+        *mouse = MouseData::from_input(&input);
+    }
+}
+
+///Event emitted by `UiInteractionSystem` for button entities
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiEvent {
+    ///Left mouse button was pressed while hovering over the button
+    Pressed(Entity),
+    ///Left mouse button was released after the button was pressed, regardless of mouse position
+    Released(Entity),
+    ///Left mouse button was pressed and released while hovering over the button
+    Clicked(Entity),
+    HoverEnter(Entity),
+    HoverLeave(Entity),
+    ///Item with given `list_id` was selected in the dropdown, entity is the dropdown parent
+    DropdownSelected(Entity, i32),
+    ///Checked state of the `Toggle` was changed by the player
+    Toggled(Entity, bool),
+}
+
+impl UiEvent {
+    ///Entity of the button that caused the event
+    pub fn entity(&self) -> Entity {
+        match self {
+            UiEvent::Pressed(entity)
+            | UiEvent::Released(entity)
+            | UiEvent::Clicked(entity)
+            | UiEvent::HoverEnter(entity)
+            | UiEvent::HoverLeave(entity)
+            | UiEvent::DropdownSelected(entity, _)
+            | UiEvent::Toggled(entity, _) => *entity,
+        }
+    }
 }
 
 ///Defines a component that will only be visible if parent is unwrapped
@@ -27,6 +108,8 @@ pub struct DropdownMenuButton {
 #[storage(VecStorage)]
 pub struct DropdownMenuParent {
     pub unwrapped: bool,
+    ///`list_id` of the last selected item
+    pub selected: Option<i32>,
 }
 
 ///Button contains texture and text info that could be used for visual actions
@@ -34,6 +117,10 @@ pub struct DropdownMenuParent {
 #[storage(VecStorage)]
 pub struct Button {
     pub hovered_over: bool,
+    ///True while button is held down by the mouse or the confirm action
+    pub pressed: bool,
+    ///True if button has keyboard/gamepad focus, focused buttons use hovered visuals
+    pub focused: bool,
     pub hovered_over_texture_name: Option<String>,
     pub hovered_over_text: Option<String>,
     pub hovered_over_color: Option<sdl2::pixels::Color>,
@@ -46,6 +133,743 @@ pub fn register_ui_components(world: &mut World) {
     world.register::<DropdownMenuButton>();
     world.register::<DropdownMenuParent>();
     world.register::<Button>();
+    world.register::<Focusable>();
+    world.register::<Toggle>();
+    world.register::<ToggleSetting>();
+    world.register::<crate::layout::Layout>();
+    world.register::<crate::layout::LayoutItem>();
+    world.register::<crate::anchor::UiTransform>();
+}
+
+///Checks if point is inside of the rectangle placed at the position
+fn contains_point(pos: &Position, rect: &Rectangle, x: i32, y: i32) -> bool {
+    x >= pos.x && y >= pos.y && x <= pos.x + rect.width && y <= pos.y + rect.height
+}
+
+///System that tracks mouse interaction with buttons and writes `UiEvent`s into the `EventChannel<UiEvent>` resource.
+/// Buttons that have hidden `Renderable` are ignored and only the button in the highest layer reacts to the mouse
+pub struct UiInteractionSystem;
+
+impl<'a> System<'a> for UiInteractionSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Rectangle>,
+        ReadStorage<'a, Renderable>,
+        WriteStorage<'a, Button>,
+        Read<'a, InputState>,
+        Write<'a, EventChannel<UiEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, pos, rect, renderable, mut button, input, mut events): Self::SystemData,
+    ) {
+        let mouse = input.mouse_position;
+        let just_pressed = input.is_mouse_just_pressed(MouseButton::Left);
+        let just_released = input.is_mouse_just_released(MouseButton::Left);
+        //only the button in the highest layer is hovered when buttons overlap
+        let topmost = (&entities, &pos, &rect, (&renderable).maybe(), &button)
+            .join()
+            .filter(|(_, pos, rect, rend, _)| {
+                rend.map(|rend| rend.is_visible()).unwrap_or(true)
+                    && contains_point(pos, rect, mouse.x, mouse.y)
+            })
+            .max_by_key(|(_, _, _, rend, _)| rend.map(|rend| rend.layer).unwrap_or_default())
+            .map(|(entity, ..)| entity);
+        for (entity, rend, button) in (&entities, (&renderable).maybe(), &mut button).join() {
+            let visible = rend.map(|rend| rend.is_visible()).unwrap_or(true);
+            let hovered = topmost == Some(entity);
+            if hovered != button.hovered_over {
+                button.hovered_over = hovered;
+                events.single_write(if hovered {
+                    UiEvent::HoverEnter(entity)
+                } else {
+                    UiEvent::HoverLeave(entity)
+                });
+            }
+            if just_pressed && hovered {
+                button.pressed = true;
+                events.single_write(UiEvent::Pressed(entity));
+            }
+            //press and release can happen during the same frame
+            if (just_released || !visible) && button.pressed {
+                button.pressed = false;
+                events.single_write(UiEvent::Released(entity));
+                if hovered {
+                    events.single_write(UiEvent::Clicked(entity));
+                }
+            }
+        }
+    }
+}
+
+///Name of the action that moves focus up, see `add_ui_actions`
+pub const UI_UP: &str = "ui_up";
+pub const UI_DOWN: &str = "ui_down";
+pub const UI_LEFT: &str = "ui_left";
+pub const UI_RIGHT: &str = "ui_right";
+///Name of the action that clicks the focused button
+pub const UI_CONFIRM: &str = "ui_confirm";
+
+///Declares actions used for UI navigation with default keyboard and gamepad bindings
+pub fn add_ui_actions(actions: &mut InputActions) {
+    use sdl2::controller::{Axis, Button as PadButton};
+    use sdl2::keyboard::Keycode;
+    actions.add_action(
+        UI_UP,
+        vec![
+            Binding::Key(Keycode::Up),
+            Binding::GamepadButton(PadButton::DPadUp),
+            Binding::GamepadAxis(Axis::LeftY, false),
+        ],
+    );
+    actions.add_action(
+        UI_DOWN,
+        vec![
+            Binding::Key(Keycode::Down),
+            Binding::GamepadButton(PadButton::DPadDown),
+            Binding::GamepadAxis(Axis::LeftY, true),
+        ],
+    );
+    actions.add_action(
+        UI_LEFT,
+        vec![
+            Binding::Key(Keycode::Left),
+            Binding::GamepadButton(PadButton::DPadLeft),
+            Binding::GamepadAxis(Axis::LeftX, false),
+        ],
+    );
+    actions.add_action(
+        UI_RIGHT,
+        vec![
+            Binding::Key(Keycode::Right),
+            Binding::GamepadButton(PadButton::DPadRight),
+            Binding::GamepadAxis(Axis::LeftX, true),
+        ],
+    );
+    actions.add_action(
+        UI_CONFIRM,
+        vec![
+            Binding::Key(Keycode::Return),
+            Binding::Key(Keycode::Space),
+            Binding::GamepadButton(PadButton::A),
+        ],
+    );
+}
+
+///Marks button as reachable by keyboard/gamepad navigation.
+/// Neighbors override spatial navigation in their direction
+#[derive(Clone, Debug, PartialEq, Component, Default)]
+#[storage(VecStorage)]
+pub struct Focusable {
+    pub up: Option<Entity>,
+    pub down: Option<Entity>,
+    pub left: Option<Entity>,
+    pub right: Option<Entity>,
+}
+
+///Resource that holds currently focused entity
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct UiFocus {
+    pub focused: Option<Entity>,
+    ///Button that is held down by the confirm action
+    pub pressed: Option<Entity>,
+}
+
+///Finds focusable entity closest to the given center in the direction.
+/// Entities that are off to the side are penalized so navigation prefers straight lines
+fn find_spatial_neighbor(
+    from: Vector2<i32>,
+    direction: Vector2<i32>,
+    candidates: &[(Entity, Vector2<i32>)],
+    current: Entity,
+) -> Option<Entity> {
+    candidates
+        .iter()
+        .filter(|(entity, _)| *entity != current)
+        .filter_map(|(entity, center)| {
+            let offset = center - from;
+            let forward = offset.dot(&direction);
+            if forward <= 0 {
+                return None;
+            }
+            let side = (offset.x * direction.y - offset.y * direction.x).abs();
+            Some((*entity, forward + side * 2))
+        })
+        .min_by_key(|(_, score)| *score)
+        .map(|(entity, _)| entity)
+}
+
+///System that moves focus between `Focusable` buttons using ui actions(see `add_ui_actions`)
+/// and clicks focused button using confirm action. Events are written the same way as in `UiInteractionSystem`.
+/// Moving the mouse over focusable button focuses it
+pub struct FocusNavigationSystem;
+
+impl<'a> System<'a> for FocusNavigationSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Rectangle>,
+        ReadStorage<'a, Renderable>,
+        ReadStorage<'a, Focusable>,
+        WriteStorage<'a, Button>,
+        Read<'a, InputActions>,
+        Read<'a, InputState>,
+        Write<'a, UiFocus>,
+        Write<'a, EventChannel<UiEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            pos,
+            rect,
+            renderable,
+            focusable,
+            mut button,
+            actions,
+            input,
+            mut focus,
+            mut events,
+        ): Self::SystemData,
+    ) {
+        let mut candidates: Vec<(Entity, Vector2<i32>)> = Vec::new();
+        let mut hovered: Option<Entity> = None;
+        for (entity, pos, rect, rend, _) in
+            (&entities, &pos, &rect, (&renderable).maybe(), &focusable).join()
+        {
+            if !rend.map(|rend| rend.is_visible()).unwrap_or(true) {
+                continue;
+            }
+            candidates.push((
+                entity,
+                Vector2::new(pos.x + rect.width / 2, pos.y + rect.height / 2),
+            ));
+            let mouse = input.mouse_position;
+            if contains_point(pos, rect, mouse.x, mouse.y) {
+                hovered = Some(entity);
+            }
+        }
+        //focused entity could have been hidden or deleted
+        if focus
+            .focused
+            .is_some_and(|focused| !candidates.iter().any(|(entity, _)| *entity == focused))
+        {
+            focus.focused = None;
+        }
+        if input.mouse_motion != Vector2::new(0, 0) && hovered.is_some() {
+            focus.focused = hovered;
+        }
+
+        let directions = [
+            (UI_UP, Vector2::new(0, -1)),
+            (UI_DOWN, Vector2::new(0, 1)),
+            (UI_LEFT, Vector2::new(-1, 0)),
+            (UI_RIGHT, Vector2::new(1, 0)),
+        ];
+        if let Some((action, direction)) = directions
+            .into_iter()
+            .find(|(action, _)| actions.just_pressed(action))
+        {
+            focus.focused = match focus.focused {
+                //start from the top left button
+                None => candidates
+                    .iter()
+                    .min_by_key(|(_, center)| (center.y, center.x))
+                    .map(|(entity, _)| *entity),
+                Some(current) => {
+                    let center = candidates
+                        .iter()
+                        .find(|(entity, _)| *entity == current)
+                        .map(|(_, center)| *center)
+                        .unwrap_or_default();
+                    //linked neighbor could be hidden or deleted
+                    let link = focusable
+                        .get(current)
+                        .and_then(|links| match action {
+                            UI_UP => links.up,
+                            UI_DOWN => links.down,
+                            UI_LEFT => links.left,
+                            _ => links.right,
+                        })
+                        .filter(|link| candidates.iter().any(|(entity, _)| entity == link));
+                    link.or_else(|| find_spatial_neighbor(center, direction, &candidates, current))
+                        .or(Some(current))
+                }
+            };
+        }
+
+        for (entity, button) in (&entities, &mut button).join() {
+            button.focused = focus.focused == Some(entity);
+        }
+        //focus moved away while confirm was held, button is released without click
+        if let Some(pressed) = focus.pressed.filter(|pressed| focus.focused != Some(*pressed)) {
+            focus.pressed = None;
+            if let Some(button) = button.get_mut(pressed).filter(|button| button.pressed) {
+                button.pressed = false;
+                events.single_write(UiEvent::Released(pressed));
+            }
+        }
+        let Some(focused) = focus.focused else {
+            return;
+        };
+        let Some(button) = button.get_mut(focused) else {
+            return;
+        };
+        if actions.just_pressed(UI_CONFIRM) {
+            button.pressed = true;
+            focus.pressed = Some(focused);
+            events.single_write(UiEvent::Pressed(focused));
+        }
+        if actions.just_released(UI_CONFIRM) && focus.pressed == Some(focused) {
+            focus.pressed = None;
+            if button.pressed {
+                button.pressed = false;
+                events.single_write(UiEvent::Released(focused));
+                events.single_write(UiEvent::Clicked(focused));
+            }
+        }
+    }
+}
+
+///System that opens and closes dropdowns and handles item selection.
+/// Clicking the parent toggles the list, clicking an item selects it and clicking anywhere else closes the list.
+/// Selection is reported with `UiEvent::DropdownSelected`
+pub struct DropdownSystem {
+    reader: ReaderId<UiEvent>,
+}
+
+impl DropdownSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            reader: world
+                .entry::<EventChannel<UiEvent>>()
+                .or_insert_with(EventChannel::new)
+                .register_reader(),
+        }
+    }
+}
+
+impl<'a> System<'a> for DropdownSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, DropdownMenuButton>,
+        WriteStorage<'a, DropdownMenuParent>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Rectangle>,
+        ReadStorage<'a, Renderable>,
+        Read<'a, InputState>,
+        Write<'a, EventChannel<UiEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, items, mut parents, pos, rect, renderable, input, mut events): Self::SystemData,
+    ) {
+        let clicked: Vec<Entity> = events
+            .read(&mut self.reader)
+            .filter_map(|event| match event {
+                UiEvent::Clicked(entity) => Some(*entity),
+                _ => None,
+            })
+            .collect();
+        let mut selected: Vec<UiEvent> = Vec::new();
+        for entity in clicked {
+            if let Some(unwrapped) = parents.get(entity).map(|parent| !parent.unwrapped) {
+                //only one list can be open at a time
+                for parent in (&mut parents).join() {
+                    parent.unwrapped = false;
+                }
+                if let Some(parent) = parents.get_mut(entity) {
+                    parent.unwrapped = unwrapped;
+                }
+            } else if let Some(item) = items.get(entity) {
+                if let Some(parent) = parents.get_mut(item.parent) {
+                    parent.selected = Some(item.list_id);
+                    parent.unwrapped = false;
+                    selected.push(UiEvent::DropdownSelected(item.parent, item.list_id));
+                }
+            }
+        }
+        events.iter_write(selected);
+
+        if input.is_mouse_just_pressed(MouseButton::Left) {
+            let mouse = input.mouse_position;
+            let over_dropdown = (&entities, &pos, &rect, &renderable)
+                .join()
+                .any(|(entity, pos, rect, rend)| {
+                    rend.is_visible()
+                        && (parents.contains(entity) || items.contains(entity))
+                        && contains_point(pos, rect, mouse.x, mouse.y)
+                });
+            if !over_dropdown {
+                for parent in (&mut parents).join() {
+                    parent.unwrapped = false;
+                }
+            }
+        }
+    }
+}
+
+///System that places dropdown items below their parent in the order of `list_id`
+/// and shows them only while the parent is visible and unwrapped
+pub struct DropdownLayoutSystem;
+
+impl<'a> System<'a> for DropdownLayoutSystem {
+    type SystemData = (
+        ReadStorage<'a, DropdownMenuButton>,
+        ReadStorage<'a, DropdownMenuParent>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Rectangle>,
+        WriteStorage<'a, Renderable>,
+    );
+
+    fn run(&mut self, (items, parents, mut pos, rect, mut renderable): Self::SystemData) {
+        use std::collections::HashMap;
+        let mut lists: HashMap<Entity, (Vector2<i32>, bool)> = HashMap::new();
+        for item in items.join() {
+            if lists.contains_key(&item.parent) {
+                continue;
+            }
+            let (Some(parent), Some(parent_pos), Some(parent_rect)) = (
+                parents.get(item.parent),
+                pos.get(item.parent),
+                rect.get(item.parent),
+            ) else {
+                continue;
+            };
+            let visible = renderable
+                .get(item.parent)
+                .map(|rend| rend.is_visible())
+                .unwrap_or(true);
+            lists.insert(
+                item.parent,
+                (
+                    Vector2::new(parent_pos.x, parent_pos.y + parent_rect.height),
+                    parent.unwrapped && visible,
+                ),
+            );
+        }
+        for (item, pos, rect, rend) in (&items, &mut pos, &rect, &mut renderable).join() {
+            let Some((origin, visible)) = lists.get(&item.parent) else {
+                continue;
+            };
+            pos.x = origin.x;
+            pos.y = origin.y + item.list_id * rect.height;
+            rend.visible = *visible;
+        }
+    }
+}
+
+///White label text placed inside of the button, used by dropdowns and toggles
+fn button_label(text: String) -> Text {
+    Text {
+        text,
+        color: sdl2::pixels::Color::WHITE,
+        visible: true,
+        offset: Vector2::new(10, 0),
+    }
+}
+
+///Creates base for the dropdown parent, items should be created using `make_dropdown_item`
+pub fn make_dropdown_parent(
+    world: &mut World,
+    location: Vector2<i32>,
+    size: Vector2<i32>,
+    label: String,
+    color: sdl2::pixels::Color,
+    layer: RenderLayers,
+) -> EntityBuilder<'_> {
+    make_button_base(world, location, size, None, color, layer)
+        .with(button_label(label))
+        .with(DropdownMenuParent::default())
+        .with(Focusable::default())
+}
+
+///Creates base for the item of the dropdown. Item is drawn in the overlay layer and is placed by `DropdownLayoutSystem`.
+/// Item is a `hierarchy::Parent` child of the dropdown parent
+pub fn make_dropdown_item(
+    world: &mut World,
+    parent: Entity,
+    list_id: i32,
+    size: Vector2<i32>,
+    text: String,
+    color: sdl2::pixels::Color,
+) -> EntityBuilder<'_> {
+    make_button_base(
+        world,
+        Vector2::new(0, 0),
+        size,
+        None,
+        color,
+        RenderLayers::Overlay,
+    )
+    .with(button_label(text))
+    .with(DropdownMenuButton { parent, list_id })
+    //makes items part of the parent's hierarchy, so they are deleted and faded together with it
+    .with(crate::hierarchy::Parent { entity: parent })
+    .with(Focusable::default())
+    //hidden until the list is opened
+    .with(Renderable::new(false, RenderLayers::Overlay as u32))
+}
+
+///Creates dropdown with one item per option, items are drawn in the overlay layer above other UI.
+/// Returns the parent entity
+pub fn make_dropdown(
+    world: &mut World,
+    location: Vector2<i32>,
+    size: Vector2<i32>,
+    label: String,
+    options: &[String],
+    color: sdl2::pixels::Color,
+    layer: RenderLayers,
+) -> Entity {
+    let parent = make_dropdown_parent(world, location, size, label, color, layer).build();
+    for (list_id, option) in options.iter().enumerate() {
+        make_dropdown_item(world, parent, list_id as i32, size, option.clone(), color).build();
+    }
+    parent
+}
+
+///Checkbox or radio button that is switched by clicking it.
+/// Sprite and text of the entity show checked state, so toggles should not use textures and texts of the `Button`
+#[derive(Clone, Debug, PartialEq, Component, Default)]
+#[storage(VecStorage)]
+pub struct Toggle {
+    pub checked: bool,
+    ///Toggles in the same group work as radio buttons, only one of them can be checked.
+    /// Clicking checked radio button doesn't uncheck it
+    pub group: Option<String>,
+    pub checked_texture_name: Option<String>,
+    pub unchecked_texture_name: Option<String>,
+    pub checked_text: Option<String>,
+    pub unchecked_text: Option<String>,
+}
+
+impl Toggle {
+    ///Checkbox that shows its state in the label
+    pub fn checkbox(label: &str, checked: bool) -> Self {
+        Self {
+            checked,
+            checked_text: Some(format!("[x] {}", label)),
+            unchecked_text: Some(format!("[ ] {}", label)),
+            ..Default::default()
+        }
+    }
+
+    ///Radio button that belongs to the group
+    pub fn radio(label: &str, group: &str, checked: bool) -> Self {
+        Self {
+            checked,
+            group: Some(group.to_owned()),
+            checked_text: Some(format!("(o) {}", label)),
+            unchecked_text: Some(format!("( ) {}", label)),
+            ..Default::default()
+        }
+    }
+
+    ///Shows state using textures, for example to draw toggle switch
+    pub fn with_textures(mut self, checked: String, unchecked: String) -> Self {
+        self.checked_texture_name = Some(checked);
+        self.unchecked_texture_name = Some(unchecked);
+        self
+    }
+
+    fn texture_name(&self) -> Option<&String> {
+        if self.checked {
+            self.checked_texture_name.as_ref()
+        } else {
+            self.unchecked_texture_name.as_ref()
+        }
+    }
+
+    fn text(&self) -> Option<&String> {
+        if self.checked {
+            self.checked_text.as_ref()
+        } else {
+            self.unchecked_text.as_ref()
+        }
+    }
+}
+
+///Binds `Toggle` to the value in the `Settings` resource. Toggle is checked while setting equals `value`.
+/// Checking the toggle writes `value` and unchecking it writes `unchecked_value`,
+/// which should be different from `value`
+#[derive(Clone, Debug, PartialEq, Component)]
+#[storage(VecStorage)]
+pub struct ToggleSetting {
+    pub name: String,
+    pub value: i32,
+    ///None for radio buttons, they are unchecked by checking another button of the group
+    pub unchecked_value: Option<i32>,
+}
+
+impl ToggleSetting {
+    ///Boolean setting, 1 when checked and 0 when unchecked
+    pub fn checkbox(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            value: 1,
+            unchecked_value: Some(0),
+        }
+    }
+
+    ///Radio button that writes its value when checked
+    pub fn radio(name: &str, value: i32) -> Self {
+        Self {
+            name: name.to_owned(),
+            value,
+            unchecked_value: None,
+        }
+    }
+}
+
+///System that switches toggles when they are clicked, keeps them in sync with bound settings
+/// and updates their sprite and text. Should run after `ButtonUpdateSystem` so it doesn't overwrite the visuals
+pub struct ToggleSystem {
+    reader: ReaderId<UiEvent>,
+}
+
+impl ToggleSystem {
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            reader: world
+                .entry::<EventChannel<UiEvent>>()
+                .or_insert_with(EventChannel::new)
+                .register_reader(),
+        }
+    }
+}
+
+impl<'a> System<'a> for ToggleSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Toggle>,
+        ReadStorage<'a, ToggleSetting>,
+        WriteStorage<'a, Sprite>,
+        WriteStorage<'a, Text>,
+        Write<'a, crate::settings::Settings>,
+        Write<'a, EventChannel<UiEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut toggles, bindings, mut sprite, mut text, mut settings, mut events): Self::SystemData,
+    ) {
+        let clicked: Vec<Entity> = events
+            .read(&mut self.reader)
+            .filter_map(|event| match event {
+                UiEvent::Clicked(entity) if toggles.contains(*entity) => Some(*entity),
+                _ => None,
+            })
+            .collect();
+        let mut changed: Vec<UiEvent> = Vec::new();
+        for entity in clicked {
+            let Some(toggle) = toggles.get_mut(entity) else {
+                continue;
+            };
+            let group = toggle.group.clone();
+            if group.is_some() && toggle.checked {
+                continue;
+            }
+            toggle.checked = !toggle.checked;
+            changed.push(UiEvent::Toggled(entity, toggle.checked));
+            let value = bindings.get(entity).and_then(|binding| {
+                if toggle.checked {
+                    Some((binding, binding.value))
+                } else {
+                    binding.unchecked_value.map(|value| (binding, value))
+                }
+            });
+            //unchanged values are not written so unrelated settings are not reapplied
+            if let Some((binding, value)) = value {
+                if settings.get(binding.name.clone()) != Some(&value) {
+                    settings.write(binding.name.clone(), value);
+                }
+            }
+            if group.is_none() {
+                continue;
+            }
+            for (other, toggle) in (&entities, &mut toggles).join() {
+                if other != entity && toggle.group == group && toggle.checked {
+                    toggle.checked = false;
+                    changed.push(UiEvent::Toggled(other, false));
+                }
+            }
+        }
+        events.iter_write(changed);
+
+        for (toggle, binding) in (&mut toggles, &bindings).join() {
+            if let Some(value) = settings.get(binding.name.clone()) {
+                toggle.checked = *value == binding.value;
+            }
+        }
+        for (toggle, sprite, text) in (&toggles, (&mut sprite).maybe(), (&mut text).maybe()).join() {
+            if let (Some(sprite), Some(name)) = (sprite, toggle.texture_name()) {
+                sprite.name = name.clone();
+            }
+            if let (Some(text), Some(label)) = (text, toggle.text()) {
+                text.text = label.clone();
+            }
+        }
+    }
+}
+
+///Creates checkbox, toggle switch or radio button. Sprite is added when toggle has textures
+pub fn make_toggle(
+    world: &mut World,
+    location: Vector2<i32>,
+    size: Vector2<i32>,
+    toggle: Toggle,
+    color: sdl2::pixels::Color,
+    layer: RenderLayers,
+) -> EntityBuilder<'_> {
+    let texture = toggle.texture_name().cloned();
+    let label = toggle.text().cloned().unwrap_or_default();
+    let builder = make_button_base(world, location, size, None, color, layer)
+        .with(button_label(label))
+        .with(Focusable::default())
+        .with(toggle);
+    match texture {
+        Some(name) => builder.with(Sprite {
+            name,
+            source_rect: None,
+            size: Vector2::new(size.x.max(0) as u32, size.y.max(0) as u32),
+            visible: true,
+        }),
+        None => builder,
+    }
+}
+
+///Creates radio group with one button per option placed from top to bottom.
+/// Buttons are bound to the setting and use its name as the group, each one writes its option index. Returns created buttons
+pub fn make_radio_group(
+    world: &mut World,
+    location: Vector2<i32>,
+    size: Vector2<i32>,
+    setting: &str,
+    options: &[String],
+    color: sdl2::pixels::Color,
+    layer: RenderLayers,
+) -> Vec<Entity> {
+    options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| {
+            make_toggle(
+                world,
+                Vector2::new(location.x, location.y + size.y * index as i32),
+                size,
+                Toggle::radio(option, setting, false),
+                color,
+                layer,
+            )
+            .with(ToggleSetting::radio(setting, index as i32))
+            .build()
+        })
+        .collect()
 }
 
 pub struct ButtonUpdateSystem;
@@ -60,12 +884,13 @@ impl<'a> System<'a> for ButtonUpdateSystem {
         WriteStorage<'a, Text>,
         WriteStorage<'a, Colored>,
         ReadStorage<'a, Button>,
-        Read<'a, MouseData>,
+        Read<'a, InputState>,
     );
     fn run(
         &mut self,
-        (rect, pos, mut sprite, mut text, mut color, button, mouse): Self::SystemData,
+        (rect, pos, mut sprite, mut text, mut color, button, input): Self::SystemData,
     ) {
+        let mouse = input.mouse_position;
         for (rect, pos, sprite, text, color, button) in (
             &rect,
             &pos,
@@ -76,11 +901,7 @@ impl<'a> System<'a> for ButtonUpdateSystem {
         )
             .join()
         {
-            if mouse.x >= pos.x
-                && mouse.y >= pos.y
-                && mouse.x <= pos.x + rect.width
-                && mouse.y <= pos.y + rect.height
-            {
+            if button.focused || contains_point(pos, rect, mouse.x, mouse.y) {
                 if let Some(sprite) = sprite {
                     if let Some(name) = &button.hovered_over_texture_name {
                         sprite.name = name.clone();
@@ -117,7 +938,8 @@ impl<'a> System<'a> for ButtonUpdateSystem {
     }
 }
 
-///Returns component of the specified type that is a button and has fully contains given point
+///Returns component of the specified type that is a button and has fully contains given point.
+/// `UiInteractionSystem` events should be preferred for handling button presses
 pub fn get_first_pressed_down_button<'a, ButtonType: specs::Component + Clone>(
     x: i32,
     y: i32,